@@ -0,0 +1,31 @@
+//! Uses the `alloc` only API so that building this crate for a target without
+//! `std` fails if anything in omnipath depends on `std`.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::string::String;
+use omnipath::windows::{clean_utf8, split_path_list, VerbatimStr, WinPathKind};
+use omnipath::WinUtf8PathBuf;
+
+pub fn kind(path: &str) -> WinPathKind {
+    WinPathKind::from_str(path)
+}
+
+pub fn push(base: &str, path: &str) -> String {
+    let mut buf = WinUtf8PathBuf::from(base);
+    buf.push(path);
+    buf.into_string()
+}
+
+pub fn clean(path: &str) -> String {
+    clean_utf8(path).into_owned()
+}
+
+pub fn verbatim_subpath(path: &str) -> Option<&str> {
+    VerbatimStr::new(path).ok().map(|verbatim| verbatim.subpath())
+}
+
+pub fn path_list_len(list: &str) -> usize {
+    split_path_list(list).count()
+}
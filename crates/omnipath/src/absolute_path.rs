@@ -0,0 +1,243 @@
+//! An owned path that is always absolute.
+
+use std::ffi::OsStr;
+use std::io;
+use std::ops::Deref;
+use std::path::{Component, Path, PathBuf};
+
+use crate::sys_absolute;
+
+/// An owned path that is always absolute and normalized.
+///
+/// The path is always in the form returned by [`sys_absolute`]. It can only be
+/// created using [`AbsolutePathBuf::resolve`] or, for paths that are already
+/// in that form, [`AbsolutePathBuf::try_from_trusted`]. Methods that change
+/// the path keep it absolute and normalized.
+///
+/// It derefs to [`Path`] so all of the usual read-only methods can be used.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::AbsolutePathBuf;
+///
+/// # fn main() -> std::io::Result<()> {
+/// #[cfg(unix)]
+/// {
+///     let mut path = AbsolutePathBuf::resolve("/path/./to".as_ref())?;
+///     path.push("../file.txt".as_ref())?;
+///     assert_eq!(path.as_path(), std::path::Path::new("/path/file.txt"));
+///
+///     // Absolute paths can't be pushed.
+///     assert!(path.push("/etc".as_ref()).is_err());
+/// }
+/// #[cfg(windows)]
+/// {
+///     let mut path = AbsolutePathBuf::resolve(r"C:\path\.\to".as_ref())?;
+///     path.push(r"..\file.txt".as_ref())?;
+///     assert_eq!(path.as_path(), std::path::Path::new(r"C:\path\file.txt"));
+///
+///     // Absolute, root relative and drive relative paths can't be pushed.
+///     assert!(path.push(r"\Windows".as_ref()).is_err());
+///     assert!(path.push(r"D:file.txt".as_ref()).is_err());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AbsolutePathBuf {
+    path: PathBuf,
+}
+impl AbsolutePathBuf {
+    /// Make a path absolute using [`sys_absolute`].
+    ///
+    /// Relative paths are resolved against the current directory.
+    pub fn resolve(path: &Path) -> io::Result<Self> {
+        Ok(Self { path: sys_absolute(path)? })
+    }
+
+    /// Use a path that is expected to already be absolute and normalized.
+    ///
+    /// An error is returned if the path is relative or if [`sys_absolute`]
+    /// would change it. This never uses the current directory.
+    pub fn try_from_trusted(path: PathBuf) -> io::Result<Self> {
+        if !path.is_absolute() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "expected an absolute path"));
+        }
+        if sys_absolute(&path)? != path {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "expected a normalized path"));
+        }
+        Ok(Self { path })
+    }
+
+    /// Join a relative path onto this path.
+    ///
+    /// `.` components are skipped and `..` components remove the last
+    /// component, the same as
+    /// [`posix_lexically_absolute`](crate::posix::PosixPathExt::posix_lexically_absolute).
+    /// The result is then normalized again using [`sys_absolute`]. This is also
+    /// true for Windows verbatim paths, so `/` in `path` is treated as a
+    /// separator and is never added to a verbatim path.
+    ///
+    /// An error is returned, and the path is left unchanged, if:
+    ///
+    /// * `path` has a root or a prefix. On Windows this includes root relative
+    ///   (e.g. `\file.txt`) and drive relative (e.g. `C:file.txt`) paths.
+    /// * A `..` component would go above the root. Use
+    ///   [`push_clamped`][AbsolutePathBuf::push_clamped] to allow this.
+    /// * On Windows, a component is a reserved device name (e.g. `NUL`), ends
+    ///   with a dot or space or contains a `:`. Normalizing would otherwise
+    ///   change these and a name like `C:y` would replace the whole path.
+    pub fn push(&mut self, path: &Path) -> io::Result<()> {
+        self.push_inner(path, false)
+    }
+
+    /// Join a relative path onto this path, ignoring `..` components that
+    /// would go above the root.
+    ///
+    /// This is the same as [`push`][AbsolutePathBuf::push] except that going
+    /// above the root is not an error. This matches how the OS resolves `..`
+    /// at the root.
+    pub fn push_clamped(&mut self, path: &Path) -> io::Result<()> {
+        self.push_inner(path, true)
+    }
+
+    fn push_inner(&mut self, path: &Path, clamp: bool) -> io::Result<()> {
+        let mut joined = self.path.clone();
+        for component in path.components() {
+            match component {
+                Component::Prefix(_) | Component::RootDir => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "only relative paths can be pushed",
+                    ))
+                }
+                Component::CurDir => {}
+                // POSIX paths may keep `..` components that follow a
+                // symlink so another `..` can't remove them.
+                Component::ParentDir if joined.ends_with("..") => joined.push(".."),
+                Component::ParentDir => {
+                    if !joined.pop() && !clamp {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "the path escapes the root",
+                        ));
+                    }
+                }
+                Component::Normal(name) => {
+                    check_name(name)?;
+                    joined.push(name);
+                }
+            }
+        }
+        self.path = sys_absolute(&joined)?;
+        Ok(())
+    }
+
+    /// Remove the last component.
+    ///
+    /// Returns `false` and does nothing if the path is a root. Like
+    /// [`push`][AbsolutePathBuf::push], a POSIX path that ends with a `..` that
+    /// was kept is never shortened. Another `..` is added instead.
+    pub fn pop(&mut self) -> bool {
+        if self.path.ends_with("..") {
+            self.path.push("..");
+            return true;
+        }
+        self.path.pop()
+    }
+
+    /// Replace the file name.
+    ///
+    /// `name` must be a single normal component. It can't be empty, contain a
+    /// separator or be `.` or `..`. On Windows it also can't be a reserved
+    /// device name, end with a dot or space or contain a `:`. An error is also
+    /// returned if the path has no file name to replace (e.g. it's a root).
+    pub fn set_file_name(&mut self, name: &OsStr) -> io::Result<()> {
+        let mut components = Path::new(name).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(normal)), None) if normal == name => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "expected a single file name",
+                ))
+            }
+        }
+        check_name(name)?;
+        if self.path.file_name().is_none() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "the path has no file name"));
+        }
+        let mut path = self.path.clone();
+        path.set_file_name(name);
+        self.path = sys_absolute(&path)?;
+        Ok(())
+    }
+
+    /// Borrow as a [`Path`].
+    pub fn as_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Convert into a [`PathBuf`].
+    pub fn into_path_buf(self) -> PathBuf {
+        self.path
+    }
+}
+
+/// Check that normalizing won't change a file name.
+fn check_name(name: &OsStr) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        use crate::windows::{is_reserved_device_name, WinPathKind};
+
+        let name = name.to_string_lossy();
+        // `PathBuf::push` replaces the whole path with a name like `C:y`. A
+        // `:` would otherwise name an alternate data stream.
+        if name.contains(':')
+            || WinPathKind::from_str(&name) != WinPathKind::CurrentDirectoryRelative
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "file names must not contain a `:` or a path prefix",
+            ));
+        }
+        if is_reserved_device_name(&name) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "file names must not be reserved device names",
+            ));
+        }
+        if name.ends_with(['.', ' ']) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "file names must not end with a dot or space",
+            ));
+        }
+    }
+    #[cfg(not(windows))]
+    let _ = name;
+    Ok(())
+}
+
+impl Deref for AbsolutePathBuf {
+    type Target = Path;
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+impl AsRef<Path> for AbsolutePathBuf {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+impl AsRef<OsStr> for AbsolutePathBuf {
+    fn as_ref(&self) -> &OsStr {
+        self.path.as_os_str()
+    }
+}
+impl From<AbsolutePathBuf> for PathBuf {
+    fn from(path: AbsolutePathBuf) -> Self {
+        path.path
+    }
+}
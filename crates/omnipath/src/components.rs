@@ -0,0 +1,219 @@
+//! Path components with the same shape on every platform.
+
+use std::ffi::OsStr;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Components, Path, Prefix};
+
+/// Iterate over the components of a path using [`SysComponent`].
+///
+/// The path is parsed using the rules of the current platform, the same as
+/// [`Path::components`]. Unlike [`std::path::Component`], matching on a
+/// [`SysComponent`] doesn't depend on the platform.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::{sys_components, SysComponent};
+/// use std::path::Path;
+///
+/// let mut depth = 0;
+/// for component in sys_components(Path::new("path/to/../file")) {
+///     match component {
+///         SysComponent::Root(_) => depth = 0,
+///         SysComponent::CurDir => {}
+///         SysComponent::ParentDir => depth -= 1,
+///         SysComponent::Normal(_) => depth += 1,
+///     }
+/// }
+/// assert_eq!(depth, 2);
+///
+/// #[cfg(unix)]
+/// {
+///     let root = sys_components(Path::new("//server/file")).next().unwrap();
+///     assert!(matches!(root, SysComponent::Root(root) if root.is_double_slash()));
+/// }
+/// #[cfg(windows)]
+/// {
+///     let mut components = sys_components(Path::new(r"\\server\share\file"));
+///     let prefix = components.next().unwrap();
+///     assert!(matches!(prefix, SysComponent::Root(root) if root.unc().is_some()));
+///     let root = components.next().unwrap();
+///     assert!(matches!(root, SysComponent::Root(root) if root.is_separator()));
+/// }
+/// ```
+pub fn sys_components(path: &Path) -> SysComponents<'_> {
+    // POSIX allows a root of exactly two slashes to have a special meaning.
+    #[cfg(unix)]
+    let double_slash =
+        matches!(path.as_os_str().as_bytes(), [b'/', b'/', rest @ ..] if !rest.starts_with(b"/"));
+    #[cfg(not(unix))]
+    let double_slash = false;
+    SysComponents { inner: path.components(), double_slash }
+}
+
+/// An iterator over the [`SysComponent`]s of a path.
+///
+/// This is created by [`sys_components`].
+#[derive(Debug, Clone)]
+pub struct SysComponents<'a> {
+    inner: Components<'a>,
+    double_slash: bool,
+}
+impl<'a> SysComponents<'a> {
+    /// Get the remaining components as a path.
+    pub fn as_path(&self) -> &'a Path {
+        self.inner.as_path()
+    }
+
+    fn convert(&self, component: Component<'a>) -> SysComponent<'a> {
+        match component {
+            Component::RootDir => {
+                SysComponent::Root(RootKind { component, double_slash: self.double_slash })
+            }
+            component => component.into(),
+        }
+    }
+}
+impl<'a> Iterator for SysComponents<'a> {
+    type Item = SysComponent<'a>;
+    fn next(&mut self) -> Option<SysComponent<'a>> {
+        let component = self.inner.next()?;
+        Some(self.convert(component))
+    }
+}
+impl<'a> DoubleEndedIterator for SysComponents<'a> {
+    fn next_back(&mut self) -> Option<SysComponent<'a>> {
+        let component = self.inner.next_back()?;
+        Some(self.convert(component))
+    }
+}
+
+/// A path component that has the same variants on every platform.
+///
+/// Platform specific details of the root are available from [`RootKind`].
+/// This can be converted to and from [`std::path::Component`]. Note that a
+/// POSIX `//` root is only detected by [`sys_components`] because it can't be
+/// told apart from `/` using a `Component`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SysComponent<'a> {
+    /// A path prefix or root separator.
+    ///
+    /// On Windows a path such as `C:\file` has two roots. The prefix `C:`
+    /// followed by the `\` separator. Either can occur without the other.
+    Root(RootKind<'a>),
+    /// A `.` component.
+    CurDir,
+    /// A `..` component.
+    ParentDir,
+    /// A file or directory name.
+    Normal(&'a OsStr),
+}
+impl<'a> SysComponent<'a> {
+    /// Get the component as it's written in the path.
+    pub fn as_os_str(self) -> &'a OsStr {
+        match self {
+            Self::Root(root) => root.as_os_str(),
+            Self::CurDir => OsStr::new("."),
+            Self::ParentDir => OsStr::new(".."),
+            Self::Normal(name) => name,
+        }
+    }
+}
+impl<'a> From<Component<'a>> for SysComponent<'a> {
+    fn from(component: Component<'a>) -> Self {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                Self::Root(RootKind { component, double_slash: false })
+            }
+            Component::CurDir => Self::CurDir,
+            Component::ParentDir => Self::ParentDir,
+            Component::Normal(name) => Self::Normal(name),
+        }
+    }
+}
+impl<'a> From<SysComponent<'a>> for Component<'a> {
+    fn from(component: SysComponent<'a>) -> Self {
+        match component {
+            SysComponent::Root(root) => root.component,
+            SysComponent::CurDir => Component::CurDir,
+            SysComponent::ParentDir => Component::ParentDir,
+            SysComponent::Normal(name) => Component::Normal(name),
+        }
+    }
+}
+
+/// The kind of root in a [`SysComponent::Root`].
+///
+/// Accessors for details that don't apply to the current platform return
+/// `false` or `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RootKind<'a> {
+    component: Component<'a>,
+    double_slash: bool,
+}
+impl<'a> RootKind<'a> {
+    /// Get the root as it's written in the path.
+    ///
+    /// A root separator is always `/` or `\`, except for a POSIX `//` root.
+    pub fn as_os_str(self) -> &'a OsStr {
+        if self.double_slash {
+            OsStr::new("//")
+        } else {
+            self.component.as_os_str()
+        }
+    }
+
+    /// Is this a root separator (`/` on POSIX, `\` on Windows).
+    pub fn is_separator(self) -> bool {
+        self.component == Component::RootDir
+    }
+
+    /// [Unix only] Is this a root of exactly two slashes.
+    ///
+    /// POSIX allows a `//` root to have an implementation defined meaning.
+    pub fn is_double_slash(self) -> bool {
+        self.double_slash
+    }
+
+    /// [Windows only] Get the path prefix.
+    pub fn prefix(self) -> Option<Prefix<'a>> {
+        match self.component {
+            Component::Prefix(prefix) => Some(prefix.kind()),
+            _ => None,
+        }
+    }
+
+    /// [Windows only] Get the drive letter of a drive prefix such as `C:` or
+    /// `\\?\C:`.
+    pub fn drive(self) -> Option<u8> {
+        match self.prefix()? {
+            Prefix::Disk(drive) | Prefix::VerbatimDisk(drive) => Some(drive),
+            _ => None,
+        }
+    }
+
+    /// [Windows only] Get the server and share names of a UNC prefix such as
+    /// `\\server\share` or `\\?\UNC\server\share`.
+    pub fn unc(self) -> Option<(&'a OsStr, &'a OsStr)> {
+        match self.prefix()? {
+            Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                Some((server, share))
+            }
+            _ => None,
+        }
+    }
+
+    /// [Windows only] Get the device name of a device prefix such as `\\.\pipe`.
+    pub fn device(self) -> Option<&'a OsStr> {
+        match self.prefix()? {
+            Prefix::DeviceNS(device) => Some(device),
+            _ => None,
+        }
+    }
+
+    /// [Windows only] Is this a verbatim prefix (starting with `\\?\`).
+    pub fn is_verbatim(self) -> bool {
+        self.prefix().map_or(false, |prefix| prefix.is_verbatim())
+    }
+}
@@ -37,7 +37,15 @@ extern crate std;
 // Utility functions and macros.
 #[macro_use]
 mod util;
+#[cfg(feature = "std")]
+mod absolute_path;
+#[cfg(feature = "std")]
+mod components;
 pub mod posix;
+#[cfg(feature = "std")]
+mod search_path;
+#[cfg(feature = "std")]
+mod shape;
 pub mod windows;
 
 #[cfg(any(doc, all(unix, feature = "std")))]
@@ -47,9 +55,22 @@ pub use posix::PosixPathExt;
 #[doc(no_inline)]
 pub use windows::WinPathExt;
 
+#[doc(no_inline)]
+pub use windows::{WinUtf8Path, WinUtf8PathBuf};
+
+#[cfg(feature = "std")]
+pub use absolute_path::AbsolutePathBuf;
+#[cfg(feature = "std")]
+pub use components::{sys_components, RootKind, SysComponent, SysComponents};
+#[cfg(feature = "std")]
+pub use search_path::SearchPath;
+#[cfg(feature = "std")]
+pub use shape::{shape, PathShape, ShapeKind};
+
 /// Converts a path to absolute according to the rules of the current platform.
 ///
 /// Unlike [`std::fs::canonicalize`] this does not resolve symlinks.
+/// An empty path is an error on every platform.
 ///
 /// # Example
 ///
@@ -73,6 +94,320 @@ pub fn sys_absolute(path: &std::path::Path) -> std::io::Result<std::path::PathBu
     return WinPathExt::win_absolute(path);
 }
 
+/// Resolves a path to both the form to pass to the OS and the form to display.
+///
+/// On Windows this is the same as [`WinPathExt::win_resolve_pair`]. On other
+/// platforms both paths are the result of [`sys_absolute`].
+///
+/// # Example
+///
+/// ```
+/// use omnipath::sys_resolve_pair;
+/// use std::path::Path;
+///
+/// let resolved = sys_resolve_pair(Path::new("path/to/file")).unwrap();
+/// #[cfg(unix)]
+/// assert_eq!(resolved.for_os, resolved.for_display);
+/// ```
+#[cfg(feature = "std")]
+pub fn sys_resolve_pair(path: &std::path::Path) -> std::io::Result<ResolvedPath> {
+    #[cfg(unix)]
+    return PosixPathExt::posix_absolute(path).map(|path| ResolvedPath::new(path.clone(), path));
+    #[cfg(windows)]
+    return WinPathExt::win_resolve_pair(path);
+}
+
+/// Converts a path to absolute and reports how it was resolved.
+///
+/// This is the same as [`sys_absolute`] but also returns a [`ResolutionKind`].
+/// The kind comes from the same classification of `path` that was used to
+/// resolve it so it always agrees with the returned path.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::{sys_absolute_tagged, ResolutionKind};
+/// use std::path::Path;
+///
+/// let (_, kind) = sys_absolute_tagged(Path::new("path/to/file")).unwrap();
+/// assert_eq!(kind, ResolutionKind::JoinedCwd);
+///
+/// #[cfg(unix)]
+/// {
+///     let (path, kind) = sys_absolute_tagged(Path::new("/path/./to/file")).unwrap();
+///     assert_eq!(path, Path::new("/path/to/file"));
+///     assert_eq!(kind, ResolutionKind::WasAbsolute);
+/// }
+/// #[cfg(windows)]
+/// {
+///     let (path, kind) = sys_absolute_tagged(Path::new(r"C:\path\.\to\file")).unwrap();
+///     assert_eq!(path, Path::new(r"C:\path\to\file"));
+///     assert_eq!(kind, ResolutionKind::WasAbsolute);
+///
+///     let (_, kind) = sys_absolute_tagged(Path::new(r"C:file")).unwrap();
+///     assert_eq!(kind, ResolutionKind::JoinedDriveCwd('C'));
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn sys_absolute_tagged(
+    path: &std::path::Path,
+) -> std::io::Result<(std::path::PathBuf, ResolutionKind)> {
+    #[cfg(unix)]
+    return posix::absolute_tagged(path);
+    #[cfg(windows)]
+    return windows::absolute_tagged(path);
+}
+
+/// How a path was made absolute by [`sys_absolute_tagged`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionKind {
+    /// The path was already absolute.
+    ///
+    /// This includes Windows device and verbatim paths.
+    WasAbsolute,
+    /// The path was joined to the current directory.
+    JoinedCwd,
+    /// [Windows only] A drive relative path (e.g. `C:file`) was joined to the
+    /// current directory for that drive.
+    JoinedDriveCwd(char),
+    /// [Windows only] A root relative path (e.g. `\file`) was joined to the
+    /// root of the current directory.
+    ///
+    /// This is the drive letter of the current directory or `None` if the
+    /// current directory isn't on a drive (e.g. it's a UNC share).
+    JoinedRoot(Option<char>),
+}
+
+/// Gets the root of an absolute path according to the rules of the current platform.
+///
+/// On Windows this is the same as [`WinPathExt::win_root`]. On other
+/// platforms this is the same as [`PosixPathExt::posix_root`]. Returns `None`
+/// if the path doesn't have a root.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::sys_root;
+/// use std::path::Path;
+///
+/// assert_eq!(sys_root(Path::new("path/to/file")), None);
+/// #[cfg(unix)]
+/// assert_eq!(sys_root(Path::new("/path/to/file")).unwrap(), Path::new("/"));
+/// #[cfg(windows)]
+/// assert_eq!(sys_root(Path::new(r"C:\path\to\file")).unwrap(), Path::new(r"C:\"));
+/// ```
+#[cfg(feature = "std")]
+pub fn sys_root(path: &std::path::Path) -> Option<std::path::PathBuf> {
+    #[cfg(unix)]
+    return PosixPathExt::posix_root(path);
+    #[cfg(windows)]
+    return WinPathExt::win_root(path);
+}
+
+/// Displays a path in the form expected by the user.
+///
+/// On Windows this is the same as [`WinPathExt::display_user`]. On other
+/// platforms this is the same as [`Path::display`](std::path::Path::display).
+///
+/// # Example
+///
+/// ```
+/// use omnipath::sys_display_user;
+/// use std::path::Path;
+///
+/// let path = Path::new("path/to/file");
+/// println!("opening {}", sys_display_user(path));
+/// ```
+#[cfg(feature = "std")]
+pub fn sys_display_user(path: &std::path::Path) -> impl core::fmt::Display + '_ {
+    #[cfg(unix)]
+    return path.display();
+    #[cfg(windows)]
+    return WinPathExt::display_user(path);
+}
+
+/// An absolute path resolved for both the OS and the user.
+///
+/// This is returned by [`sys_resolve_pair`] and [`WinPathExt::win_resolve_pair`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPath {
+    /// The path to pass to OS APIs.
+    pub for_os: std::path::PathBuf,
+    /// The path to show to the user.
+    pub for_display: std::path::PathBuf,
+    /// `true` if `for_display` can't be displayed without loss (e.g. because
+    /// it isn't valid Unicode).
+    pub was_lossy: bool,
+}
+#[cfg(feature = "std")]
+impl ResolvedPath {
+    pub(crate) fn new(for_os: std::path::PathBuf, for_display: std::path::PathBuf) -> Self {
+        let was_lossy = for_display.to_str().is_none();
+        Self { for_os, for_display, was_lossy }
+    }
+}
+
+/// Join an untrusted relative path onto a base path.
+///
+/// Unlike [`Path::join`](std::path::Path::join), this will never replace the
+/// base path. It returns an error if `entry` is anything other than a plain
+/// relative path or if it uses `..` components to escape the base path.
+/// Otherwise `.` and `..` components are resolved lexically and the result is
+/// joined to `base`. The base path itself is not changed so it may be relative.
+///
+/// The entry and each of its components are classified using
+/// [`WinPathKind`](windows::WinPathKind) on every platform. So on POSIX,
+/// `C:\file` and `dir/C:file` are rejected in addition to `/file`. This is
+/// useful for paths taken from archives, which may have been created on
+/// another platform.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::{join_onto, JoinRejected};
+/// use omnipath::windows::WinPathKind;
+/// use std::path::Path;
+///
+/// let base = Path::new("staging");
+/// assert_eq!(
+///     join_onto(base, Path::new("path/./to/../file")).unwrap(),
+///     Path::new("staging/path/file")
+/// );
+///
+/// assert_eq!(join_onto(base, Path::new("/etc/passwd")), Err(JoinRejected::NotRelative(WinPathKind::RootRelative)));
+/// assert_eq!(join_onto(base, Path::new(r"C:\evil")), Err(JoinRejected::NotRelative(WinPathKind::Drive(b'C' as u16))));
+/// assert_eq!(join_onto(base, Path::new("path/../../evil")), Err(JoinRejected::EscapesBase));
+/// ```
+#[cfg(feature = "std")]
+pub fn join_onto(
+    base: &std::path::Path,
+    entry: &std::path::Path,
+) -> Result<std::path::PathBuf, JoinRejected> {
+    use std::path::Component;
+
+    let kind = windows::WinPathKind::from_str(&entry.to_string_lossy());
+    if kind != windows::WinPathKind::CurrentDirectoryRelative {
+        return Err(JoinRejected::NotRelative(kind));
+    }
+    let mut joined = base.to_path_buf();
+    let mut depth = 0_usize;
+    for component in entry.components() {
+        match component {
+            Component::Normal(name) => {
+                // On Windows, pushing a component such as `C:file` replaces
+                // the whole path.
+                let kind = windows::WinPathKind::from_str(&name.to_string_lossy());
+                if kind != windows::WinPathKind::CurrentDirectoryRelative {
+                    return Err(JoinRejected::NotRelative(kind));
+                }
+                depth += 1;
+                joined.push(name);
+            }
+            Component::ParentDir => {
+                if depth == 0 {
+                    return Err(JoinRejected::EscapesBase);
+                }
+                depth -= 1;
+                joined.pop();
+            }
+            Component::CurDir => {}
+            // Already rejected by the kind check.
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(JoinRejected::NotRelative(kind));
+            }
+        }
+    }
+    Ok(joined)
+}
+
+/// The error returned by [`join_onto`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinRejected {
+    /// The entry is not a plain relative path. This holds the kind of path found.
+    NotRelative(windows::WinPathKind),
+    /// The entry uses `..` to escape the base path.
+    EscapesBase,
+}
+#[cfg(feature = "std")]
+impl core::fmt::Display for JoinRejected {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotRelative(kind) => write!(f, "expected a relative path but found {:?}", kind),
+            Self::EscapesBase => f.write_str("the path escapes the base directory"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for JoinRejected {}
+
+/// Test if [`sys_absolute`] may return a different path.
+///
+/// This is a quick check that can be used to skip normalizing paths that are
+/// already absolute and clean. It looks for relative paths, `.` and `..`
+/// components and repeated separators. On Windows it also looks for `/`
+/// separators, trailing dots or spaces, DOS device names and any path prefix
+/// other than a drive or UNC path.
+///
+/// This may return `true` for some paths that would not be changed but if it
+/// returns `false` then the path is guaranteed to be unchanged by
+/// [`sys_absolute`].
+///
+/// # Example
+///
+/// ```
+/// use omnipath::needs_normalization;
+/// use std::path::Path;
+///
+/// assert!(needs_normalization(Path::new("path/to/file")));
+/// assert!(needs_normalization(Path::new("/path/./to/file")));
+/// #[cfg(unix)]
+/// assert!(!needs_normalization(Path::new("/path/to/file")));
+/// #[cfg(windows)]
+/// assert!(!needs_normalization(Path::new(r"C:\path\to\file")));
+/// ```
+#[cfg(feature = "std")]
+pub fn needs_normalization(path: &std::path::Path) -> bool {
+    #[cfg(unix)]
+    return posix::needs_normalization(path);
+    #[cfg(windows)]
+    return windows::needs_normalization(path);
+}
+
+/// The point where a path leaves the directory it names.
+///
+/// This is returned by [`WinPathExt::win_divergence`] and
+/// [`PosixPathExt::posix_divergence`]. For example, `C:\logs\..\Windows`
+/// names the `C:\logs` directory but the `..` component leaves it.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The index of the `..` component in the path's components.
+    pub index: usize,
+    /// The directory named by the components before the `..`.
+    pub left: std::path::PathBuf,
+}
+
+/// Find the first `..` component that leaves a directory named by the path.
+#[cfg(feature = "std")]
+pub(crate) fn find_divergence(path: &std::path::Path) -> Option<Divergence> {
+    use std::path::Component;
+
+    let mut left = std::path::PathBuf::new();
+    let mut has_normal = false;
+    for (index, component) in path.components().enumerate() {
+        match component {
+            Component::ParentDir if has_normal => return Some(Divergence { index, left }),
+            Component::Normal(_) => has_normal = true,
+            _ => {}
+        }
+        left.push(component);
+    }
+    None
+}
+
 /// Canonicalizes a path.
 ///
 /// This is the same as [`std::fs::canonicalize`] but on Windows this attempts
@@ -97,3 +432,179 @@ pub fn sys_canonicalize(path: &std::path::Path) -> std::io::Result<std::path::Pa
     #[cfg(windows)]
     return path.canonicalize()?.to_winuser_path();
 }
+
+/// Make a string into a file name that is valid on both Windows and POSIX.
+///
+/// This applies the rules of [`windows::sanitize_component`]: reserved
+/// characters, control characters and trailing dots and spaces are replaced
+/// and reserved DOS device names are changed. Windows is stricter than POSIX,
+/// so this also replaces the `/` and null characters that POSIX forbids and
+/// never returns an empty name, `.` or `..`.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::sanitize_portable;
+///
+/// // Valid on POSIX but not on Windows.
+/// assert_eq!(sanitize_portable("12:30 notes", '_'), "12_30 notes");
+/// assert_eq!(sanitize_portable("aux.c", '_'), "aux_.c");
+/// // Not valid anywhere.
+/// assert_eq!(sanitize_portable("AC/DC", '_'), "AC_DC");
+/// ```
+pub fn sanitize_portable(name: &str, replacement: char) -> alloc::string::String {
+    windows::sanitize_component(name, replacement)
+}
+
+/// The file name rules used by [`is_valid_filename`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Platform {
+    /// Windows file names, see [`windows::sanitize_component`].
+    Windows,
+    /// POSIX file names, which can contain anything except `/` and null.
+    Posix,
+    /// File names that are valid on both Windows and POSIX.
+    Portable,
+}
+
+/// Test if a string can be used as a single file name on a platform.
+///
+/// A valid name is never empty, `.` or `..`. The name is valid if the
+/// matching sanitize function ([`windows::sanitize_component`],
+/// [`posix::sanitize_component_posix`] or [`sanitize_portable`]) would return
+/// it unchanged. Length limits are not checked.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::{is_valid_filename, Platform};
+///
+/// assert!(is_valid_filename("notes.txt", Platform::Portable));
+/// assert!(is_valid_filename("12:30 notes", Platform::Posix));
+/// assert!(!is_valid_filename("12:30 notes", Platform::Windows));
+/// assert!(!is_valid_filename("nul.txt", Platform::Portable));
+/// assert!(!is_valid_filename("AC/DC", Platform::Posix));
+/// ```
+pub fn is_valid_filename(name: &str, platform: Platform) -> bool {
+    fn is_valid_windows(name: &str) -> bool {
+        !name.is_empty()
+            && !name.contains(windows::is_reserved_char)
+            && !name.ends_with(['.', ' '])
+            && !windows::is_reserved_device_name(name)
+    }
+    fn is_valid_posix(name: &str) -> bool {
+        !matches!(name, "" | "." | "..") && !name.contains(['/', '\0'])
+    }
+    match platform {
+        Platform::Windows => is_valid_windows(name),
+        Platform::Posix => is_valid_posix(name),
+        // Every valid Windows name is also a valid POSIX name.
+        Platform::Portable => is_valid_windows(name),
+    }
+}
+
+/// Test if a path contains any control characters.
+///
+/// Control characters such as newlines are valid in POSIX file names and in
+/// Windows verbatim paths but they can be used to spoof log output or break
+/// line based protocols.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::contains_control_chars;
+///
+/// assert!(contains_control_chars("path/to\nfile"));
+/// assert!(contains_control_chars("path/to/\u{1b}[31mfile"));
+/// assert!(!contains_control_chars("path/to/file"));
+/// ```
+pub fn contains_control_chars(path: &str) -> bool {
+    path.chars().any(char::is_control)
+}
+
+/// The separator used between paths in a `PATH`-like list on the current platform.
+///
+/// This is `;` on Windows and `:` on other platforms.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::PATH_LIST_SEPARATOR;
+///
+/// #[cfg(windows)]
+/// assert_eq!(PATH_LIST_SEPARATOR, ';');
+/// #[cfg(not(windows))]
+/// assert_eq!(PATH_LIST_SEPARATOR, ':');
+/// ```
+#[cfg(windows)]
+pub const PATH_LIST_SEPARATOR: char = windows::PATH_LIST_SEPARATOR;
+#[cfg(not(windows))]
+pub const PATH_LIST_SEPARATOR: char = ':';
+
+/// Split a `PATH`-like list into its entries using the rules of the current platform.
+///
+/// On Windows this is the same as [`windows::split_path_list`]. On other
+/// platforms entries are separated by `:` and there is no quoting. Empty
+/// entries are returned as empty strings.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::split_path_list;
+///
+/// #[cfg(windows)]
+/// assert_eq!(
+///     split_path_list(r#"C:\bin;"C:\a;b""#).collect::<Vec<_>>(),
+///     [r"C:\bin", r"C:\a;b"]
+/// );
+/// #[cfg(not(windows))]
+/// assert_eq!(split_path_list("/usr/bin:/bin").collect::<Vec<_>>(), ["/usr/bin", "/bin"]);
+/// ```
+pub fn split_path_list(list: &str) -> impl Iterator<Item = &str> + '_ {
+    #[cfg(windows)]
+    return windows::split_path_list(list);
+    #[cfg(not(windows))]
+    return list.split(PATH_LIST_SEPARATOR);
+}
+
+/// Join paths into a `PATH`-like list using the rules of the current platform.
+///
+/// On Windows this is the same as [`windows::join_path_list`], which quotes
+/// entries containing `;`. On other platforms entries are joined with `:`.
+/// There is no way to quote a `:` on these platforms so entries containing
+/// one are joined as-is and won't round-trip through [`split_path_list`].
+///
+/// # Example
+///
+/// ```
+/// use omnipath::{join_path_list, split_path_list};
+///
+/// #[cfg(windows)]
+/// let entries = [r"C:\Program Files\bin", r"C:\a;b"];
+/// #[cfg(not(windows))]
+/// let entries = ["/usr/local/my bin", "/bin"];
+///
+/// let list = join_path_list(entries);
+/// let list = list.to_str().unwrap();
+/// assert_eq!(split_path_list(list).collect::<Vec<_>>(), entries);
+/// ```
+#[cfg(feature = "std")]
+pub fn join_path_list<I, P>(paths: I) -> std::ffi::OsString
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<std::path::Path>,
+{
+    #[cfg(windows)]
+    return windows::join_path_list(paths);
+    #[cfg(not(windows))]
+    {
+        let mut list = std::ffi::OsString::new();
+        for (index, path) in paths.into_iter().enumerate() {
+            if index != 0 {
+                list.push(":");
+            }
+            list.push(path.as_ref());
+        }
+        list
+    }
+}
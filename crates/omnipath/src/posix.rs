@@ -1,16 +1,21 @@
 #![cfg(any(doc, all(unix, feature = "std")))]
 use std::env;
+use std::ffi::OsStr;
+use std::fmt;
 use std::io;
 #[cfg(not(doc))]
 use std::os::unix::ffi::OsStrExt;
 use std::path::Component;
 use std::path::{Path, PathBuf};
+use std::string::String;
+
+use crate::{Divergence, ResolutionKind};
 
 pub trait PosixPathExt: Sealed {
     /// [Unix only] Make a POSIX path absolute without changing its semantics.
     ///
     /// Unlike canonicalize the path does not need to exist. Symlinks and `..`
-    /// components will not be resolved.
+    /// components will not be resolved. An empty path is an error.
     ///
     /// # Example
     ///
@@ -141,14 +146,160 @@ pub trait PosixPathExt: Sealed {
     /// }
     /// ```
     fn posix_lexically_absolute_from(&self, cwd: &Path) -> io::Result<PathBuf>;
+
+    /// [Unix only] Make a POSIX path lexically absolute without leaving `root`.
+    ///
+    /// Relative paths are resolved relative to `root`. `..` components are
+    /// resolved by popping the parent component, the same as
+    /// [`posix_lexically_absolute_from`][PosixPathExt::posix_lexically_absolute_from].
+    /// However, an error is returned if the path is outside of `root` or if a
+    /// `..` component would climb above `root`, even if a later component
+    /// returns to it.
+    ///
+    /// This can be used to check that a path stays within a directory without
+    /// accessing the filesystem. Note that symlinks are not resolved so this
+    /// is not sufficient to sandbox paths that are then passed to the OS.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(unix)]
+    /// {
+    ///     use omnipath::posix::PosixPathExt;
+    ///     use std::path::Path;
+    ///     let root = Path::new("/srv/data");
+    ///
+    ///     let path = Path::new("path/to/..//./file");
+    ///     assert_eq!(
+    ///         &path.posix_lexically_absolute_bounded(root).unwrap(),
+    ///         Path::new("/srv/data/path/file")
+    ///     );
+    ///
+    ///     let path = Path::new("/srv/data/path/../file");
+    ///     assert_eq!(
+    ///         &path.posix_lexically_absolute_bounded(root).unwrap(),
+    ///         Path::new("/srv/data/file")
+    ///     );
+    ///
+    ///     // These would escape the root.
+    ///     assert!(Path::new("path/../../file").posix_lexically_absolute_bounded(root).is_err());
+    ///     assert!(Path::new("/srv/file").posix_lexically_absolute_bounded(root).is_err());
+    ///     assert!(Path::new("/a/../../b").posix_lexically_absolute_bounded("/".as_ref()).is_err());
+    /// }
+    /// ```
+    fn posix_lexically_absolute_bounded(&self, root: &Path) -> io::Result<PathBuf>;
+
+    /// [Unix only] Get the path that the OS will use.
+    ///
+    /// This is the same as [`posix_lexically_absolute`][PosixPathExt::posix_lexically_absolute].
+    /// It is intended for comparing with the path as it was written (see
+    /// [`posix_divergence`][PosixPathExt::posix_divergence]). Note that the OS
+    /// resolves `..` after following symlinks so this is only accurate for
+    /// paths that do not contain symlinks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(unix)]
+    /// {
+    ///     use omnipath::posix::PosixPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new("/var/log/../lib/file");
+    ///     assert_eq!(path.posix_effective().unwrap(), Path::new("/var/lib/file"));
+    /// }
+    /// ```
+    fn posix_effective(&self) -> io::Result<PathBuf>;
+
+    /// [Unix only] Find the first `..` component that leaves a directory named by the path.
+    ///
+    /// For example `/var/log/../lib` names the `/var/log` directory but
+    /// actually refers to `/var/lib`. Returns `None` if the path never leaves
+    /// a directory that it names. This does not access the filesystem.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(unix)]
+    /// {
+    ///     use omnipath::posix::PosixPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let divergence = Path::new("/var/log/../lib/file").posix_divergence().unwrap();
+    ///     assert_eq!(divergence.index, 3);
+    ///     assert_eq!(divergence.left, Path::new("/var/log"));
+    ///
+    ///     assert!(Path::new("/var/log/file").posix_divergence().is_none());
+    ///     assert!(Path::new("../file").posix_divergence().is_none());
+    /// }
+    /// ```
+    fn posix_divergence(&self) -> Option<Divergence>;
+
+    /// [Unix only] Get the root of an absolute path.
+    ///
+    /// This is `/` or, if the path starts with exactly two slashes, `//`.
+    /// POSIX allows the `//` root to have an implementation defined meaning so
+    /// it's kept distinct. Returns `None` for relative paths.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(unix)]
+    /// {
+    ///     use omnipath::posix::PosixPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     assert_eq!(Path::new("/path/to/file").posix_root().unwrap(), Path::new("/"));
+    ///     assert_eq!(Path::new("//path/to/file").posix_root().unwrap(), Path::new("//"));
+    ///     assert_eq!(Path::new("///path/to/file").posix_root().unwrap(), Path::new("/"));
+    ///     assert_eq!(Path::new("path/to/file").posix_root(), None);
+    /// }
+    /// ```
+    fn posix_root(&self) -> Option<PathBuf>;
+
+    /// [Unix only] Display the path with only the file name quoted, and only
+    /// if it needs to be.
+    ///
+    /// The directory part is written as-is so it stays readable. The file name
+    /// is quoted using [`sh_quote`] if it contains whitespace, control
+    /// characters, quotes, glob characters (`*?[]`) or other shell
+    /// metacharacters, or if it isn't valid UTF-8. Use
+    /// [`HybridDisplay::quote_all`] to quote the whole path instead.
+    ///
+    /// Nothing is allocated unless the file name is quoted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(unix)]
+    /// {
+    ///     use omnipath::posix::PosixPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new("out dir/build/my file.txt");
+    ///     assert_eq!(path.posix_display_hybrid().to_string(), "out dir/build/'my file.txt'");
+    ///     assert_eq!(
+    ///         path.posix_display_hybrid().quote_all(true).to_string(),
+    ///         "'out dir/build/my file.txt'"
+    ///     );
+    ///     assert_eq!(Path::new("build/app.log").posix_display_hybrid().to_string(), "build/app.log");
+    /// }
+    /// ```
+    fn posix_display_hybrid(&self) -> HybridDisplay<'_>;
 }
 
 impl PosixPathExt for Path {
     fn posix_absolute(&self) -> io::Result<PathBuf> {
+        if self.as_os_str().is_empty() {
+            return Err(empty_error());
+        }
         posix_absolute_from(self, env::current_dir)
     }
 
     fn posix_lexically_absolute(&self) -> io::Result<PathBuf> {
+        if self.as_os_str().is_empty() {
+            return Err(empty_error());
+        }
         posix_lexically_absolute_from(self, env::current_dir)
     }
 
@@ -156,6 +307,9 @@ impl PosixPathExt for Path {
         if !cwd.is_absolute() {
             return Err(cwd_error());
         }
+        if self.as_os_str().is_empty() {
+            return Err(empty_error());
+        }
         posix_absolute_from(self, || posix_absolute_from(cwd, || unreachable!()))
     }
 
@@ -163,10 +317,368 @@ impl PosixPathExt for Path {
         if !cwd.is_absolute() {
             return Err(cwd_error());
         }
+        if self.as_os_str().is_empty() {
+            return Err(empty_error());
+        }
         posix_lexically_absolute_from(self, || {
             posix_lexically_absolute_from(cwd, || unreachable!())
         })
     }
+
+    fn posix_lexically_absolute_bounded(&self, root: &Path) -> io::Result<PathBuf> {
+        if !root.is_absolute() {
+            return Err(root_error());
+        }
+        let root = posix_lexically_absolute_from(root, || unreachable!())?;
+        let path = posix_absolute_from(self, || Ok(root.clone()))?;
+        let rest = path.strip_prefix(&root).map_err(|_| escape_error())?;
+
+        let mut normalized = root.clone();
+        let mut depth = 0_usize;
+        for component in rest.components() {
+            match component {
+                Component::ParentDir => {
+                    if depth == 0 {
+                        return Err(escape_error());
+                    }
+                    depth -= 1;
+                    normalized.pop();
+                }
+                Component::CurDir => {}
+                component => {
+                    depth += 1;
+                    normalized.push(component);
+                }
+            }
+        }
+        if self.as_os_str().as_bytes().ends_with(b"/") {
+            normalized.push("");
+        }
+        Ok(normalized)
+    }
+
+    fn posix_effective(&self) -> io::Result<PathBuf> {
+        self.posix_lexically_absolute()
+    }
+
+    fn posix_divergence(&self) -> Option<Divergence> {
+        crate::find_divergence(self)
+    }
+
+    fn posix_root(&self) -> Option<PathBuf> {
+        match self.as_os_str().as_bytes() {
+            [b'/', b'/', b'/', ..] => Some(PathBuf::from("/")),
+            [b'/', b'/', ..] => Some(PathBuf::from("//")),
+            [b'/', ..] => Some(PathBuf::from("/")),
+            _ => None,
+        }
+    }
+
+    fn posix_display_hybrid(&self) -> HybridDisplay<'_> {
+        HybridDisplay { path: self, quote_all: false }
+    }
+}
+
+/// [Unix only] Display a path with its file name quoted when needed.
+///
+/// This is created by [`PosixPathExt::posix_display_hybrid`].
+#[derive(Debug, Clone, Copy)]
+pub struct HybridDisplay<'a> {
+    path: &'a Path,
+    quote_all: bool,
+}
+impl<'a> HybridDisplay<'a> {
+    /// Quote the whole path using [`sh_quote`] instead of only the file name.
+    ///
+    /// Unlike the file name, the whole path is always quoted.
+    pub fn quote_all(mut self, quote_all: bool) -> Self {
+        self.quote_all = quote_all;
+        self
+    }
+}
+impl fmt::Display for HybridDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.quote_all {
+            return f.write_str(&sh_quote(self.path));
+        }
+        let bytes = self.path.as_os_str().as_bytes();
+        let name_start = bytes.iter().rposition(|&b| b == b'/').map_or(0, |i| i + 1);
+        let (parent, name) = bytes.split_at(name_start);
+        let parent = Path::new(OsStr::from_bytes(parent));
+        let name = Path::new(OsStr::from_bytes(name));
+        if needs_shell_quotes(name) {
+            write!(f, "{}{}", parent.display(), sh_quote(name))
+        } else {
+            write!(f, "{}{}", parent.display(), name.display())
+        }
+    }
+}
+
+/// Quotes, glob characters and other shell metacharacters.
+const SHELL_SPECIAL: &str = "'\"`$\\*?[]|&;<>(){}!#~";
+
+/// Test if a file name must be quoted to be used as part of a shell word.
+fn needs_shell_quotes(name: &Path) -> bool {
+    let name = match name.to_str() {
+        Some(name) => name,
+        None => return true,
+    };
+    name.chars().any(|c| c.is_whitespace() || c.is_control() || SHELL_SPECIAL.contains(c))
+}
+
+/// [Unix only] Quote a path so it can be used as a single word in a POSIX shell.
+///
+/// The path is wrapped in single quotes and any embedded single quotes are
+/// written as `'\''`. No other characters are special within single quotes.
+///
+/// Single quotes can't contain arbitrary bytes so a path that is not valid
+/// UTF-8 is instead written using `$'...'` quoting, with `\xHH` escapes for
+/// any bytes that are not printable ASCII. This form is supported by most
+/// shells and was standardized in POSIX.1-2024 but some older shells do not
+/// support it.
+///
+/// # Example
+///
+/// ```
+/// #[cfg(unix)]
+/// {
+///     use omnipath::posix::sh_quote;
+///     use std::path::Path;
+///
+///     assert_eq!(sh_quote(Path::new("/path/to/my file")), "'/path/to/my file'");
+///     assert_eq!(sh_quote(Path::new("/path/to/it's")), r"'/path/to/it'\''s'");
+///     assert_eq!(sh_quote(Path::new("/path/to/\nfile")), "'/path/to/\nfile'");
+///
+///     use std::ffi::OsStr;
+///     use std::os::unix::ffi::OsStrExt;
+///     let path = Path::new(OsStr::from_bytes(b"/path/to/\xFFfile's"));
+///     assert_eq!(sh_quote(path), r"$'/path/to/\xfffile\'s'");
+/// }
+/// ```
+pub fn sh_quote(path: &Path) -> String {
+    use std::fmt::Write;
+
+    if let Some(path) = path.to_str() {
+        let mut quoted = String::with_capacity(path.len() + 2);
+        quoted.push('\'');
+        for c in path.chars() {
+            if c == '\'' {
+                quoted.push_str(r"'\''");
+            } else {
+                quoted.push(c);
+            }
+        }
+        quoted.push('\'');
+        return quoted;
+    }
+
+    let path = path.as_os_str().as_bytes();
+    let mut quoted = String::with_capacity(path.len() + 3);
+    quoted.push_str("$'");
+    for &b in path {
+        match b {
+            b'\'' | b'\\' => {
+                quoted.push('\\');
+                quoted.push(b as char);
+            }
+            b' '..=b'~' => quoted.push(b as char),
+            _ => {
+                let _ = write!(quoted, "\\x{:02x}", b);
+            }
+        }
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// [Unix only] Make a string into a valid file name by replacing anything
+/// POSIX doesn't allow.
+///
+/// The only bytes that can't be used in a POSIX file name are `/` and the
+/// null byte. These are replaced with `replacement`. An empty name, `.` or
+/// `..` would not name a new file so each character is replaced (an empty name
+/// becomes `replacement`). Leading dots are otherwise kept so hidden files can
+/// still be named.
+///
+/// If `replacement` is itself `/`, null or `.` then `_` is used instead. A `.`
+/// could otherwise turn a name into `.` or `..`.
+///
+/// # Example
+///
+/// ```
+/// #[cfg(unix)]
+/// {
+///     use omnipath::posix::sanitize_component_posix;
+///
+///     assert_eq!(sanitize_component_posix("AC/DC", '_'), "AC_DC");
+///     assert_eq!(sanitize_component_posix("null\0byte", '-'), "null-byte");
+///     assert_eq!(sanitize_component_posix("..", '_'), "__");
+///     assert_eq!(sanitize_component_posix(".hidden", '_'), ".hidden");
+/// }
+/// ```
+pub fn sanitize_component_posix(name: &str, replacement: char) -> String {
+    let replacement = match replacement {
+        '/' | '\0' | '.' => '_',
+        c => c,
+    };
+    match name {
+        "" => return replacement.into(),
+        "." | ".." => return name.chars().map(|_| replacement).collect(),
+        _ => {}
+    }
+    name.chars().map(|c| if matches!(c, '/' | '\0') { replacement } else { c }).collect()
+}
+
+/// The root directories checked by [`posix_is_pseudo_fs`].
+pub const PSEUDO_FS_ROOTS: &[&str] = &["proc", "sys", "dev"];
+
+/// [Unix only] Test if an absolute path points into a pseudo filesystem such as `/proc`.
+///
+/// This is true if the first component of the lexically normalized path is
+/// one of [`PSEUDO_FS_ROOTS`]. Relative paths always return `false`.
+///
+/// This does not access the filesystem so symlinks are not resolved. Use
+/// [`posix_is_pseudo_fs_with`] to check a different set of roots.
+///
+/// # Example
+///
+/// ```
+/// #[cfg(unix)]
+/// {
+///     use omnipath::posix::posix_is_pseudo_fs;
+///     use std::path::Path;
+///
+///     assert!(posix_is_pseudo_fs(Path::new("/proc/self")));
+///     assert!(posix_is_pseudo_fs(Path::new("/a/../proc")));
+///     assert!(!posix_is_pseudo_fs(Path::new("/home")));
+///     assert!(!posix_is_pseudo_fs(Path::new("proc/self")));
+/// }
+/// ```
+pub fn posix_is_pseudo_fs(path: &Path) -> bool {
+    posix_is_pseudo_fs_with(path, PSEUDO_FS_ROOTS)
+}
+
+/// [Unix only] Test if an absolute path points into one of the given root directories.
+///
+/// See [`posix_is_pseudo_fs`] for details.
+///
+/// # Example
+///
+/// ```
+/// #[cfg(unix)]
+/// {
+///     use omnipath::posix::posix_is_pseudo_fs_with;
+///     use std::path::Path;
+///
+///     let roots = ["proc", "run"];
+///     assert!(posix_is_pseudo_fs_with(Path::new("/run/user"), &roots));
+///     assert!(!posix_is_pseudo_fs_with(Path::new("/dev/null"), &roots));
+/// }
+/// ```
+pub fn posix_is_pseudo_fs_with(path: &Path, roots: &[&str]) -> bool {
+    if !path.is_absolute() {
+        return false;
+    }
+    // Find the first component after resolving `..` components.
+    let mut depth = 0_usize;
+    let mut first = None;
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => {
+                if depth == 0 {
+                    first = Some(name);
+                }
+                depth += 1;
+            }
+            Component::ParentDir => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    first = None;
+                }
+            }
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    first.map_or(false, |first| roots.iter().any(|root| first == *root))
+}
+
+/// [Unix only] Test if a path is already lexically normal.
+///
+/// A normal path doesn't have any `.` components or repeated separators and
+/// `..` is only allowed at the start of a relative path, where it can't be
+/// resolved lexically. A root of exactly two slashes (`//`) is normal because
+/// POSIX allows it to have a special meaning. A trailing slash is also normal.
+/// `.` on its own is normal but an empty path is not.
+///
+/// If this returns `true` for an absolute path then
+/// [`posix_lexically_absolute`](PosixPathExt::posix_lexically_absolute)
+/// returns the path unchanged.
+///
+/// # Example
+///
+/// ```
+/// #[cfg(unix)]
+/// {
+///     use omnipath::posix::posix_is_normal;
+///     use std::path::Path;
+///
+///     assert!(posix_is_normal(Path::new("/path/to/file")));
+///     assert!(posix_is_normal(Path::new("//server/file")));
+///     assert!(posix_is_normal(Path::new("../../file")));
+///
+///     assert!(!posix_is_normal(Path::new("/path/./file")));
+///     assert!(!posix_is_normal(Path::new("/path//file")));
+///     assert!(!posix_is_normal(Path::new("///path/file")));
+///     assert!(!posix_is_normal(Path::new("path/../file")));
+/// }
+/// ```
+pub fn posix_is_normal(path: &Path) -> bool {
+    let bytes = path.as_os_str().as_bytes();
+    let rest = match bytes {
+        [] => return false,
+        b"." => return true,
+        [b'/', b'/', b'/', ..] => return false,
+        [b'/', b'/', rest @ ..] | [b'/', rest @ ..] => rest,
+        rest => rest,
+    };
+    if rest.is_empty() {
+        return true;
+    }
+    let is_relative = !bytes.starts_with(b"/");
+    let mut components = rest.split(|&b| b == b'/').peekable();
+    // Only leading `..` components of a relative path can't be resolved.
+    let mut only_parents = true;
+    while let Some(component) = components.next() {
+        let is_last = components.peek().is_none();
+        match component {
+            // A trailing slash.
+            b"" if is_last => {}
+            b"" | b"." => return false,
+            b".." if is_relative && only_parents => {}
+            b".." => return false,
+            _ => only_parents = false,
+        }
+    }
+    true
+}
+
+/// Test if `posix_absolute` may change the path.
+///
+/// This errs on the side of returning `true`.
+pub(crate) fn needs_normalization(path: &Path) -> bool {
+    let path = path.as_os_str().as_bytes();
+    if !path.starts_with(b"/") {
+        return true;
+    }
+    path.windows(2).any(|w| w == b"//")
+        || path.split(|&b| b == b'/').any(|component| component == b"." || component == b"..")
+}
+
+/// `posix_absolute` tagged with how the path was resolved.
+pub(crate) fn absolute_tagged(path: &Path) -> io::Result<(PathBuf, ResolutionKind)> {
+    let kind =
+        if path.is_absolute() { ResolutionKind::WasAbsolute } else { ResolutionKind::JoinedCwd };
+    path.posix_absolute().map(|path| (path, kind))
 }
 
 fn cwd_error() -> io::Error {
@@ -176,6 +688,18 @@ fn cwd_error() -> io::Error {
     )
 }
 
+fn empty_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "an empty path cannot be made absolute")
+}
+
+fn root_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "expected an absolute path as the root")
+}
+
+fn escape_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "the path escapes the root")
+}
+
 mod private {
     pub trait Sealed {}
     impl Sealed for std::path::Path {}
@@ -0,0 +1,212 @@
+//! An ordered list of directories, such as the `PATH` environment variable.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::slice;
+use std::vec::Vec;
+
+use crate::{join_path_list, split_path_list, sys_absolute};
+
+/// An ordered list of absolute directories without duplicates.
+///
+/// Entries are made absolute using [`sys_absolute`] when they're added. Two
+/// entries are considered the same if they are equal according to the rules
+/// of the current platform. On Windows this ignores ASCII case and verbatim
+/// paths are compared as user paths where possible.
+///
+/// Entries are kept in priority order, starting with the highest priority.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::SearchPath;
+///
+/// # fn main() -> std::io::Result<()> {
+/// #[cfg(unix)]
+/// {
+///     let mut search = SearchPath::from_list("/usr/bin:/bin".as_ref())?;
+///     assert!(!search.push_unique("/usr/bin/")?);
+///     assert!(search.prepend_unique("/opt/bin")?);
+///     assert!(search.remove("/bin"));
+///     assert_eq!(search.to_env_value(), "/opt/bin:/usr/bin");
+/// }
+/// #[cfg(windows)]
+/// {
+///     let mut search = SearchPath::from_list(r"C:\Windows;C:\bin".as_ref())?;
+///     assert!(!search.push_unique(r"\\?\c:\windows")?);
+///     assert!(search.prepend_unique(r"C:\Program Files\app")?);
+///     assert!(search.remove(r"c:\BIN"));
+///     assert_eq!(search.to_env_value(), r"C:\Program Files\app;C:\Windows");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SearchPath {
+    paths: Vec<PathBuf>,
+    /// The normalized form of each path in `paths`, used for comparisons.
+    keys: Vec<PathBuf>,
+}
+impl SearchPath {
+    /// Create an empty search path.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a search path from an environment variable.
+    ///
+    /// The variable is split using [`split_path_list`]. Empty entries and
+    /// duplicates are skipped. If the variable is not set then the search
+    /// path is empty.
+    pub fn from_env<K: AsRef<OsStr>>(var: K) -> io::Result<Self> {
+        match env::var_os(var) {
+            Some(list) => Self::from_list(&list),
+            None => Ok(Self::new()),
+        }
+    }
+
+    /// Create a search path from a `PATH`-like list.
+    ///
+    /// See [`SearchPath::from_env`].
+    pub fn from_list(list: &OsStr) -> io::Result<Self> {
+        let mut search = Self::new();
+        match list.to_str() {
+            Some(list) => {
+                for path in split_path_list(list).filter(|path| !path.is_empty()) {
+                    search.push_unique(path)?;
+                }
+            }
+            // `split_path_list` needs UTF-8.
+            None => {
+                for path in env::split_paths(list).filter(|path| !path.as_os_str().is_empty()) {
+                    search.push_unique(path)?;
+                }
+            }
+        }
+        Ok(search)
+    }
+
+    /// Add a path with the lowest priority, unless it's already present.
+    ///
+    /// Returns `true` if the path was added.
+    pub fn push_unique<P: AsRef<Path>>(&mut self, path: P) -> io::Result<bool> {
+        let path = sys_absolute(path.as_ref())?;
+        let key = entry_key(&path);
+        if self.position(&key).is_some() {
+            return Ok(false);
+        }
+        self.paths.push(path);
+        self.keys.push(key);
+        Ok(true)
+    }
+
+    /// Add a path with the highest priority.
+    ///
+    /// If the path is already present then it's moved to the front. Returns
+    /// `true` if the path was not already present.
+    pub fn prepend_unique<P: AsRef<Path>>(&mut self, path: P) -> io::Result<bool> {
+        let path = sys_absolute(path.as_ref())?;
+        let key = entry_key(&path);
+        match self.position(&key) {
+            Some(index) => {
+                self.paths[..=index].rotate_right(1);
+                self.keys[..=index].rotate_right(1);
+                Ok(false)
+            }
+            None => {
+                self.paths.insert(0, path);
+                self.keys.insert(0, key);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Remove a path. Returns `true` if the path was present.
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) -> bool {
+        let index =
+            sys_absolute(path.as_ref()).ok().and_then(|path| self.position(&entry_key(&path)));
+        match index {
+            Some(index) => {
+                self.paths.remove(index);
+                self.keys.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Test if a path is present.
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        sys_absolute(path.as_ref()).ok().and_then(|path| self.position(&entry_key(&path))).is_some()
+    }
+
+    /// Iterate over the paths in priority order.
+    pub fn iter(&self) -> slice::Iter<'_, PathBuf> {
+        self.paths.iter()
+    }
+
+    /// The number of paths.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns `true` if there are no paths.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Join the paths into a value suitable for a `PATH`-like environment variable.
+    ///
+    /// The paths are joined using [`join_path_list`].
+    pub fn to_env_value(&self) -> OsString {
+        join_path_list(&self.paths)
+    }
+
+    fn position(&self, key: &Path) -> Option<usize> {
+        self.keys.iter().position(|entry| same_key(entry, key))
+    }
+}
+impl<'a> IntoIterator for &'a SearchPath {
+    type Item = &'a PathBuf;
+    type IntoIter = slice::Iter<'a, PathBuf>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Normalize an absolute path so it can be compared using [`same_key`].
+///
+/// Verbatim paths are converted to user paths where possible.
+#[cfg(windows)]
+fn entry_key(path: &Path) -> PathBuf {
+    use crate::WinPathExt;
+
+    path.to_winuser_path().unwrap_or_else(|_| path.into())
+}
+
+/// Normalize an absolute path so it can be compared using [`same_key`].
+#[cfg(not(windows))]
+fn entry_key(path: &Path) -> PathBuf {
+    path.into()
+}
+
+/// Test if two keys created by [`entry_key`] are the same entry.
+#[cfg(windows)]
+fn same_key(a: &Path, b: &Path) -> bool {
+    let (mut a, mut b) = (a.components(), b.components());
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(a), Some(b)) if a.as_os_str().eq_ignore_ascii_case(b.as_os_str()) => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Test if two keys created by [`entry_key`] are the same entry.
+#[cfg(not(windows))]
+fn same_key(a: &Path, b: &Path) -> bool {
+    a == b
+}
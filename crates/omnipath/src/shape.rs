@@ -0,0 +1,181 @@
+//! Summarize the structure of a path without revealing its contents.
+
+use core::fmt;
+use std::path::{Component, Path};
+
+/// Summarize the structure of a path.
+///
+/// The returned [`PathShape`] does not contain any part of the path's names so
+/// it can be logged or reported where the path itself must not be. The path
+/// is examined in a single pass and the OS is not accessed.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::{shape, ShapeKind};
+/// use std::path::Path;
+///
+/// #[cfg(windows)]
+/// let path = Path::new(r"C:\Users\secret\Documents\report.txt");
+/// #[cfg(not(windows))]
+/// let path = Path::new("/Users/secret/Documents/report.txt");
+///
+/// let shape = shape(path);
+/// assert_eq!(shape.depth, 4);
+/// assert_eq!(shape.max_component_len, 10);
+/// assert!(!shape.to_string().contains("secret"));
+/// #[cfg(windows)]
+/// assert_eq!(shape.to_string(), "Drive/d4/max10/ascii/clean");
+/// #[cfg(not(windows))]
+/// assert_eq!(shape.to_string(), "Absolute/d4/max10/ascii/clean");
+/// ```
+pub fn shape(path: &Path) -> PathShape {
+    let mut shape = PathShape {
+        kind: ShapeKind::of(path),
+        depth: 0,
+        max_component_len: 0,
+        length_histogram: [0; 5],
+        non_ascii: false,
+        trailing_dot: false,
+    };
+    for component in path.components() {
+        let name = match component {
+            Component::Normal(name) => name,
+            // `..` counts towards the depth but has no name to measure.
+            Component::ParentDir => {
+                shape.depth += 1;
+                continue;
+            }
+            _ => continue,
+        };
+        let len = name.len();
+        shape.depth += 1;
+        shape.max_component_len = shape.max_component_len.max(len);
+        let bucket = match len {
+            0..=8 => 0,
+            9..=16 => 1,
+            17..=32 => 2,
+            33..=64 => 3,
+            _ => 4,
+        };
+        shape.length_histogram[bucket] += 1;
+        match name.to_str() {
+            Some(name) => {
+                shape.non_ascii |= !name.is_ascii();
+                shape.trailing_dot |= name.ends_with(['.', ' ']) && name != ".." && name != ".";
+            }
+            // Names that aren't valid Unicode are never ASCII.
+            None => {
+                shape.non_ascii = true;
+                shape.trailing_dot |= name.to_string_lossy().ends_with(['.', ' ']);
+            }
+        }
+    }
+    shape
+}
+
+/// The structure of a path.
+///
+/// This is returned by [`shape`]. It never contains any part of the path's
+/// names. The [`Display`](fmt::Display) implementation writes a compact
+/// summary such as `Drive/d5/max32/ascii/clean`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathShape {
+    /// The kind of path.
+    pub kind: ShapeKind,
+    /// The number of components. This doesn't include the prefix, root or `.`
+    /// components but does include `..` components. The other fields only
+    /// count named components.
+    pub depth: usize,
+    /// The length of the longest named component, in bytes.
+    pub max_component_len: usize,
+    /// The number of named components with lengths in the ranges `0..=8`,
+    /// `9..=16`, `17..=32`, `33..=64` and `65..`.
+    pub length_histogram: [u32; 5],
+    /// `true` if any component contains non-ASCII characters or is not valid Unicode.
+    pub non_ascii: bool,
+    /// `true` if any component ends with a `.` or a space. On Windows these
+    /// are trimmed unless the path is verbatim.
+    pub trailing_dot: bool,
+}
+impl PathShape {
+    /// Returns `true` if this is a Windows verbatim path.
+    pub fn is_verbatim(&self) -> bool {
+        self.kind == ShapeKind::Verbatim
+    }
+}
+impl fmt::Display for PathShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/d{}/max{}/{}/{}",
+            self.kind.as_str(),
+            self.depth,
+            self.max_component_len,
+            if self.non_ascii { "nonascii" } else { "ascii" },
+            if self.trailing_dot { "trailingdot" } else { "clean" },
+        )
+    }
+}
+
+/// The kind of path summarized by a [`PathShape`].
+///
+/// On Windows this is the same as the [`WinPathKind`](crate::windows::WinPathKind)
+/// but without the drive letter. On other platforms paths are either
+/// [`Absolute`](ShapeKind::Absolute) or [`Relative`](ShapeKind::Relative).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShapeKind {
+    /// A Windows drive path such as `C:\`.
+    Drive,
+    /// A Windows UNC path such as `\\server\share`.
+    Unc,
+    /// A Windows device path such as `\\.\COM1`.
+    Device,
+    /// A Windows verbatim path such as `\\?\C:\`.
+    Verbatim,
+    /// A Windows drive relative path such as `C:file`.
+    DriveRelative,
+    /// A Windows root relative path such as `\file`.
+    RootRelative,
+    /// An absolute path on platforms other than Windows.
+    Absolute,
+    /// A path relative to the current directory.
+    Relative,
+}
+impl ShapeKind {
+    #[cfg(windows)]
+    fn of(path: &Path) -> Self {
+        use crate::windows::WinPathKind;
+        match WinPathKind::from_os_str(path.as_os_str()) {
+            WinPathKind::Drive(_) => Self::Drive,
+            WinPathKind::Unc => Self::Unc,
+            WinPathKind::Device => Self::Device,
+            WinPathKind::Verbatim => Self::Verbatim,
+            WinPathKind::DriveRelative(_) => Self::DriveRelative,
+            WinPathKind::RootRelative => Self::RootRelative,
+            WinPathKind::CurrentDirectoryRelative => Self::Relative,
+        }
+    }
+    #[cfg(not(windows))]
+    fn of(path: &Path) -> Self {
+        if path.is_absolute() {
+            Self::Absolute
+        } else {
+            Self::Relative
+        }
+    }
+
+    /// The name of the kind, as used by `PathShape`'s `Display` implementation.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Drive => "Drive",
+            Self::Unc => "Unc",
+            Self::Device => "Device",
+            Self::Verbatim => "Verbatim",
+            Self::DriveRelative => "DriveRelative",
+            Self::RootRelative => "RootRelative",
+            Self::Absolute => "Absolute",
+            Self::Relative => "Relative",
+        }
+    }
+}
@@ -46,22 +46,44 @@ macro_rules! match_pattern {
 
 /// Trim `len` bytes from the start, `const` edition.
 ///
-/// This should only be used for small sizes of `len`.
-pub const fn trim_start(bytes: &[u8], len: u8) -> &[u8] {
-    if len == 0 {
-        return bytes;
-    }
-    match bytes {
-        [_, bytes @ ..] => trim_start(bytes, len - 1),
-        [] => bytes,
+/// If `len` is greater than the length of `bytes` then an empty slice is returned.
+pub const fn trim_start(mut bytes: &[u8], mut len: usize) -> &[u8] {
+    while len > 0 {
+        match bytes {
+            [_, rest @ ..] => bytes = rest,
+            [] => break,
+        }
+        len -= 1;
     }
+    bytes
 }
 
 /// Trim `len` bytes from the start of a `&str`.
 ///
 /// SAFETY: The trimmed str must be valid UTF-8.
 pub const unsafe fn trim_start_str(s: &str, len: usize) -> &str {
-    core::str::from_utf8_unchecked(trim_start(s.as_bytes(), len as u8))
+    core::str::from_utf8_unchecked(trim_start(s.as_bytes(), len))
+}
+
+/// Split a `&str` at `mid`, `const` edition.
+///
+/// SAFETY: `mid` must be on a char boundary and no greater than the length of `s`.
+pub const unsafe fn split_str_at(s: &str, mid: usize) -> (&str, &str) {
+    let mut head = s.as_bytes();
+    let mut tail = s.as_bytes();
+    while head.len() > mid {
+        if let [rest @ .., _] = head {
+            head = rest;
+        }
+    }
+    let mut i = 0;
+    while i < mid {
+        if let [_, rest @ ..] = tail {
+            tail = rest;
+        }
+        i += 1;
+    }
+    (core::str::from_utf8_unchecked(head), core::str::from_utf8_unchecked(tail))
 }
 
 /// Get the length of a UTF-8 encoded scalar by examining the leading byte.
@@ -75,21 +97,25 @@ pub const fn utf8_len(first_byte: u8) -> u8 {
 /// Convert a BMP UTF-8 encoded code point to a UTF-16 encoded code point.
 ///
 /// While it is safe to call this with random bytes or non-BMP code points,
-/// the result is unspecified.
+/// the result is unspecified. This never panics.
 pub const fn bmp_utf8_to_utf16(bytes: &[u8]) -> u16 {
-    debug_assert!(matches!(utf8_len(bytes[0]), 1 | 2 | 3));
-    match utf8_len(bytes[0]) {
-        3.. => {
-            let a = (bytes[0] & 0b1111) as u16;
-            let b = (bytes[1] & 0b111111) as u16;
-            let c = (bytes[2] & 0b111111) as u16;
+    let len = match bytes {
+        [first, ..] => utf8_len(*first),
+        [] => return 0,
+    };
+    match bytes {
+        [a, b, c, ..] if len >= 3 => {
+            let a = (*a & 0b1111) as u16;
+            let b = (*b & 0b111111) as u16;
+            let c = (*c & 0b111111) as u16;
             (a << 12) | (b << 6) | c
         }
-        2.. => {
-            let a = (bytes[0] & 0b11111) as u16;
-            let b = (bytes[1] & 0b111111) as u16;
+        [a, b, ..] if len >= 2 => {
+            let a = (*a & 0b11111) as u16;
+            let b = (*b & 0b111111) as u16;
             (a << 6) | b
         }
-        _ => bytes[0] as u16,
+        [a, ..] => *a as u16,
+        [] => 0,
     }
 }
@@ -1,8 +1,53 @@
+//! Windows path parsing and conversions.
+//!
+//! Functions that are not marked as Windows only parse paths as strings.
+//! These never panic, whatever input they are given, so they're safe to use on
+//! untrusted paths.
+
+#[cfg(any(doc, all(windows, feature = "std")))]
+pub mod batch;
+mod clean;
+mod display;
 pub(crate) mod kind;
+mod list;
+mod name;
+#[cfg(feature = "std")]
+mod pipeline;
+#[cfg(feature = "std")]
+mod quote;
 #[cfg(any(doc, all(windows, feature = "std")))]
 mod sys;
+mod utf8;
+#[cfg(any(doc, all(windows, feature = "std")))]
+mod wide;
 
+pub(crate) use name::is_reserved_char;
+#[cfg(any(doc, all(windows, feature = "std")))]
+pub use pipeline::PipelineOutput;
+#[cfg(feature = "std")]
+pub use pipeline::{Pipeline, PipelineConflict, PipelineStep};
+#[cfg(all(windows, feature = "std"))]
+pub(crate) use sys::{absolute_tagged, needs_normalization};
+#[cfg(any(doc, all(windows, feature = "std")))]
+pub use sys::{
+    resolve_prefix, DisplaySafe, DisplayUser, PathProblem, PreparedPath, WinFileParts, WinPathExt,
+};
 #[cfg(any(doc, all(windows, feature = "std")))]
-pub use sys::{resolve_prefix, WinPathExt};
+pub use wide::{wide_chunks, WideChunks, WideScanner};
 
-pub use kind::{Win32Relative, WinPathKind};
+pub use clean::{clean_utf8, is_win_clean};
+pub use display::{to_shell_display, truncate_for_log};
+pub use kind::{
+    compose, device_path_kind, drive_root, ensure_drive_root, has_mixed_separators, same_unc_share,
+    verbatim_has_forward_slash, ComposeError, DeviceKind, VerbatimParseError, VerbatimStr,
+    Win32Absolute, Win32Relative, WinPathKind, WinVerbatimKind,
+};
+#[cfg(feature = "std")]
+pub use list::join_path_list;
+pub use list::{split_path_list, PATH_LIST_SEPARATOR};
+#[cfg(feature = "std")]
+pub use name::has_reserved_device_name;
+pub use name::{is_reserved_device_name, looks_like_short_name, sanitize_component};
+#[cfg(feature = "std")]
+pub use quote::cmd_quote;
+pub use utf8::{WinRegions, WinUtf8Path, WinUtf8PathBuf};
@@ -0,0 +1,92 @@
+//! [Windows only] Convert many paths at once.
+
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use super::display::{self, UserPrefix};
+
+/// [Windows only] Convert verbatim paths to user paths in place.
+///
+/// This is the same as calling [`to_winuser_path`](super::WinPathExt::to_winuser_path)
+/// on each path except that it never calls the Windows API. Instead each path
+/// is checked lexically and converted only if the user path is guaranteed to
+/// refer to the same file. The check is conservative so some paths may be
+/// reported as lossy even though they could be converted. Device paths (e.g.
+/// `\\?\pipe\name`) are always reported as lossy.
+///
+/// Converting a path only ever removes or replaces part of its prefix so each
+/// path's existing allocation is reused.
+///
+/// # Example
+///
+/// ```
+/// #[cfg(windows)]
+/// {
+///     use omnipath::windows::batch::to_winuser_all;
+///     use std::path::{Path, PathBuf};
+///
+///     let mut paths = vec![
+///         PathBuf::from(r"\\?\C:\path\to\file.txt"),
+///         PathBuf::from(r"\\?\UNC\server\share\file.txt"),
+///         PathBuf::from(r"C:\path\to\file.txt"),
+///         PathBuf::from(r"\\?\C:\path\to\file."),
+///     ];
+///     let report = to_winuser_all(&mut paths);
+///     assert_eq!(
+///         paths,
+///         [
+///             PathBuf::from(r"C:\path\to\file.txt"),
+///             PathBuf::from(r"\\server\share\file.txt"),
+///             PathBuf::from(r"C:\path\to\file.txt"),
+///             // The trailing `.` would be removed from a user path.
+///             PathBuf::from(r"\\?\C:\path\to\file."),
+///         ]
+///     );
+///     assert_eq!((report.converted, report.kept, report.lossy), (2, 1, 1));
+/// }
+/// ```
+pub fn to_winuser_all(paths: &mut [PathBuf]) -> BatchReport {
+    let mut report = BatchReport::default();
+    for path in paths {
+        match user_prefix(path) {
+            UserPrefix::Kept => report.kept += 1,
+            UserPrefix::Lossy => report.lossy += 1,
+            UserPrefix::Replace { len, with } => {
+                let os_string = mem::take(path).into_os_string();
+                match os_string.into_string() {
+                    Ok(mut string) => {
+                        string.replace_range(..len, with);
+                        *path = string.into();
+                        report.converted += 1;
+                    }
+                    Err(os_string) => {
+                        // Unreachable because the prefix was checked as a `str`.
+                        *path = os_string.into();
+                        report.kept += 1;
+                    }
+                }
+            }
+        }
+    }
+    report
+}
+
+/// [Windows only] The result of [`to_winuser_all`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    /// The number of verbatim paths that were converted to user paths.
+    pub converted: usize,
+    /// The number of paths that were left unchanged because they were not
+    /// verbatim or were not valid Unicode.
+    pub kept: usize,
+    /// The number of verbatim paths left unchanged because converting them
+    /// may change which file they refer to.
+    pub lossy: usize,
+}
+
+fn user_prefix(path: &Path) -> UserPrefix {
+    match path.to_str() {
+        Some(path) => display::user_prefix(path),
+        None => UserPrefix::Kept,
+    }
+}
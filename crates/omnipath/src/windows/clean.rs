@@ -0,0 +1,249 @@
+//! Lexically clean Windows paths without using the Windows API.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::kind::{ParsedUtf8Path, WinPathKind};
+use super::name::is_reserved_device_name;
+
+/// Clean a Windows path without accessing the OS.
+///
+/// This works on all platforms and follows the same rules that Windows uses
+/// when making a path absolute, except that relative paths stay relative:
+///
+/// * `/` separators are converted to `\` and repeated separators are collapsed.
+/// * `.` components are removed.
+/// * `..` components remove the previous component. They can't remove the
+///   root or UNC share but leading `..` components of a relative path are kept.
+///   For device paths only the `\\.\` is a root so the device name can be
+///   removed.
+/// * If a component ends with a single `.` then that `.` is removed. So `dir.`
+///   becomes `dir` but `dir..` is unchanged.
+/// * All trailing dots and spaces are removed from the last component. If
+///   nothing is left then the path ends with a separator.
+/// * Verbatim paths are returned unchanged.
+///
+/// Unlike [`WinPathExt::win_absolute`](crate::windows::WinPathExt::win_absolute)
+/// this does not turn DOS device names such as `NUL` into device paths. If
+/// all components of a relative path are removed then the result is `.`.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::clean_utf8;
+///
+/// assert_eq!(clean_utf8(r"C:\path\.\to\\..\file.txt. "), r"C:\path\file.txt");
+/// assert_eq!(clean_utf8(r"C:\path.\dir..\file.txt"), r"C:\path\dir..\file.txt");
+/// assert_eq!(clean_utf8(r"//server/share/../file.txt"), r"\\server\share\file.txt");
+/// assert_eq!(clean_utf8(r"\\.\pipe\..\COM1"), r"\\.\COM1");
+/// assert_eq!(clean_utf8(r"..\path\..\..\file.txt"), r"..\..\file.txt");
+/// assert_eq!(clean_utf8(r"\\?\C:\path\..\file.txt"), r"\\?\C:\path\..\file.txt");
+/// ```
+pub fn clean_utf8(path: &str) -> Cow<'_, str> {
+    clean_utf8_with(path, true)
+}
+
+/// Clean a path the same as [`clean_utf8`] but optionally keep trailing dots
+/// and spaces.
+///
+/// When `trim` is `false`, every component except `.` and `..` is kept exactly
+/// as written.
+pub(crate) fn clean_utf8_with(path: &str, trim: bool) -> Cow<'_, str> {
+    let parsed = ParsedUtf8Path::from_utf8(path);
+    let kind = parsed.kind();
+    if kind == WinPathKind::Verbatim {
+        return Cow::Borrowed(path);
+    }
+    let (prefix, subpath) = parsed.parts();
+    let components = subpath.split(['\\', '/']).filter(|c| !c.is_empty());
+
+    let is_relative =
+        matches!(kind, WinPathKind::CurrentDirectoryRelative | WinPathKind::DriveRelative(_));
+    let mut stack: Vec<&str> = Vec::new();
+    // The number of leading `..` components in `stack`.
+    let mut parents = 0;
+    for component in components {
+        match component {
+            "." => {}
+            ".." => {
+                if stack.len() > parents {
+                    stack.pop();
+                } else if is_relative {
+                    stack.push("..");
+                    parents += 1;
+                }
+            }
+            _ => {
+                let component = if trim { trim_single_dot(component) } else { component };
+                stack.push(component);
+            }
+        }
+    }
+    let mut has_trailing_separator = subpath.ends_with(['\\', '/']);
+    // The last component loses all trailing dots and spaces.
+    if trim && !has_trailing_separator && stack.len() > parents {
+        if let Some(last) = stack.last_mut() {
+            *last = last.trim_end_matches(['.', ' ']);
+            if last.is_empty() {
+                stack.pop();
+                has_trailing_separator = true;
+            }
+        }
+    }
+
+    let mut cleaned = String::with_capacity(path.len());
+    // Keep the spelling of a device prefix.
+    let written = parsed.normalized_str_kind();
+    let written = written.as_written();
+    cleaned.push_str(written);
+    let rest = prefix.get(written.len()..).unwrap_or_default();
+    cleaned.extend(rest.chars().map(|c| if c == '/' { '\\' } else { c }));
+    // UNC paths need a separator between the share name and the components.
+    let needs_separator = kind == WinPathKind::Unc;
+    for (index, component) in stack.iter().enumerate() {
+        if index != 0 || needs_separator {
+            cleaned.push('\\');
+        }
+        cleaned.push_str(component);
+    }
+    if cleaned.is_empty() {
+        cleaned.push('.');
+    } else if (!stack.is_empty() || needs_separator) && has_trailing_separator {
+        cleaned.push('\\');
+    }
+
+    if cleaned == path {
+        Cow::Borrowed(path)
+    } else {
+        Cow::Owned(cleaned)
+    }
+}
+
+/// Remove a single trailing `.` unless another `.` comes before it.
+fn trim_single_dot(component: &str) -> &str {
+    match component.strip_suffix('.') {
+        Some(trimmed) if !trimmed.ends_with('.') => trimmed,
+        _ => component,
+    }
+}
+
+/// Test if a path is already clean.
+///
+/// This returns `true` if [`clean_utf8`] would return the path unchanged but
+/// it doesn't allocate. A clean path only uses `\` separators, doesn't have
+/// repeated separators or `.` components and doesn't have any components that
+/// end with a single `.`. The last component can't end with any dots or
+/// spaces. `..` is only allowed at the start of a relative path. Verbatim paths
+/// are always clean.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::is_win_clean;
+///
+/// assert!(is_win_clean(r"C:\path\to\file.txt"));
+/// assert!(is_win_clean(r"C:\path..\to \file.txt"));
+/// assert!(is_win_clean(r"..\..\file.txt"));
+/// assert!(is_win_clean(r"\\?\C:\path\..\file.txt."));
+///
+/// assert!(!is_win_clean(r"C:/path/to/file.txt"));
+/// assert!(!is_win_clean(r"C:\path\\to\.\file.txt"));
+/// assert!(!is_win_clean(r"C:\path\..\file.txt"));
+/// assert!(!is_win_clean(r"C:\path.\file.txt"));
+/// assert!(!is_win_clean(r"C:\path\file.txt. "));
+/// ```
+pub fn is_win_clean(path: &str) -> bool {
+    let parsed = ParsedUtf8Path::from_utf8(path);
+    let kind = parsed.kind();
+    if kind == WinPathKind::Verbatim || path == "." {
+        return true;
+    }
+    if path.is_empty() {
+        return false;
+    }
+    let (prefix, subpath) = parsed.parts();
+    let written = parsed.normalized_str_kind();
+    let written = written.as_written();
+    if !prefix.starts_with(written) || prefix[written.len()..].contains('/') {
+        return false;
+    }
+    if subpath.contains('/') {
+        return false;
+    }
+
+    // UNC paths have a separator between the share name and the components.
+    let needs_separator = kind == WinPathKind::Unc;
+    let body = match kind {
+        WinPathKind::Unc if subpath.is_empty() => return true,
+        WinPathKind::Unc => match subpath.strip_prefix('\\') {
+            Some(body) => body,
+            None => return false,
+        },
+        _ => subpath,
+    };
+    if body.is_empty() {
+        return true;
+    }
+    let is_relative =
+        matches!(kind, WinPathKind::CurrentDirectoryRelative | WinPathKind::DriveRelative(_));
+    let mut components = body.split('\\').peekable();
+    // Only leading `..` components of a relative path are kept.
+    let mut only_parents = true;
+    let mut is_first = true;
+    while let Some(component) = components.next() {
+        let is_last = components.peek().is_none();
+        let is_clean = match component {
+            // A trailing separator is kept unless it's all that's left.
+            "" => is_last && (!is_first || needs_separator),
+            "." => false,
+            ".." => is_relative && only_parents,
+            _ if is_last => {
+                only_parents = false;
+                !component.ends_with(['.', ' '])
+            }
+            _ => {
+                only_parents = false;
+                trim_single_dot(component) == component
+            }
+        };
+        if !is_clean {
+            return false;
+        }
+        is_first = false;
+    }
+    true
+}
+
+/// Test if `win_absolute` may change a path that has already been split into
+/// its kind and the rest of the path.
+///
+/// This errs on the side of returning `true`.
+pub(crate) fn subpath_needs_normalization(kind: WinPathKind, subpath: &str) -> bool {
+    let is_drive_or_unc = match kind {
+        WinPathKind::Drive(drive) => (b'A' as u16..=b'Z' as u16).contains(&drive),
+        WinPathKind::Unc => true,
+        _ => false,
+    };
+    if !is_drive_or_unc || subpath.contains('/') {
+        return true;
+    }
+    let mut components = subpath.split('\\').enumerate().peekable();
+    while let Some((index, component)) = components.next() {
+        if component.is_empty() {
+            // Only a trailing separator is allowed, except that UNC paths
+            // must have both a server and a share name.
+            let is_last = components.peek().is_none();
+            if !is_last || (kind == WinPathKind::Unc && index < 2) {
+                return true;
+            }
+        } else if component == "."
+            || component == ".."
+            || component.ends_with(['.', ' '])
+            || is_reserved_device_name(component)
+        {
+            return true;
+        }
+    }
+    kind == WinPathKind::Unc && !subpath.contains('\\')
+}
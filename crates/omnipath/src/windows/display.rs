@@ -0,0 +1,189 @@
+//! Helpers for displaying Windows paths to the user.
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+
+use super::clean::subpath_needs_normalization;
+use super::kind::{
+    is_verbatim_str, verbatim_has_forward_slash, ParsedUtf8Path, VerbatimParseError, Win32Absolute,
+    WinPathKind,
+};
+
+/// Shorten a path so that it's no more than `max_chars` characters long.
+///
+/// This is intended for logging or other diagnostics where long paths need to
+/// be truncated. If the path is too long then:
+///
+/// 1. A verbatim drive or UNC path is converted to a user path by removing
+///    the verbatim prefix.
+/// 2. Components are removed from the middle of the path and replaced with
+///    `…`. The path prefix (e.g. `C:\` or `\\server\share`) and as many
+///    trailing components as possible are kept.
+/// 3. If the prefix doesn't leave enough room then it's also removed.
+/// 4. If even the last component doesn't fit then only the end of it is kept.
+///
+/// The path is only ever cut between characters. Note that stripping the
+/// verbatim prefix does not check that the user path refers to the same file
+/// so the result should only be used for display.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::truncate_for_log;
+///
+/// let path = r"\\?\C:\Users\user\Documents\projects\omnipath\src\lib.rs";
+/// assert_eq!(truncate_for_log(path, 30), r"C:\…\omnipath\src\lib.rs");
+/// assert_eq!(truncate_for_log(path, 10), r"…\lib.rs");
+/// assert_eq!(truncate_for_log(path, 4), r"….rs");
+///
+/// // Short paths are unchanged.
+/// assert_eq!(truncate_for_log(r"\\?\C:\file.txt", 30), r"\\?\C:\file.txt");
+/// ```
+pub fn truncate_for_log(path: &str, max_chars: usize) -> Cow<'_, str> {
+    const ELLIPSIS: char = '…';
+
+    if path.chars().count() <= max_chars {
+        return Cow::Borrowed(path);
+    }
+    if max_chars == 0 {
+        return Cow::Borrowed("");
+    }
+    let path: Cow<'_, str> = match Win32Absolute::from_verbatim_str(path) {
+        Ok(_) if verbatim_has_forward_slash(path) => Cow::Borrowed(path),
+        Ok((Win32Absolute::Drive(_), subpath)) => Cow::Borrowed(subpath),
+        Ok((Win32Absolute::Unc, subpath)) if subpath.starts_with('\\') => {
+            Cow::Owned(format!(r"\{}", subpath))
+        }
+        _ => Cow::Borrowed(path),
+    };
+    if path.chars().count() <= max_chars {
+        return path;
+    }
+
+    // Keep the prefix and as many whole components from the end as will fit.
+    let parsed = ParsedUtf8Path::from_utf8(&path);
+    let (prefix, subpath) = parsed.parts();
+    let prefix_sep = if prefix.is_empty() || prefix.ends_with(['\\', '/']) { "" } else { r"\" };
+    let head_len = prefix.chars().count() + prefix_sep.len() + r"…\".chars().count();
+    if let Some(tail) = fitting_tail(subpath, max_chars.saturating_sub(head_len)) {
+        return Cow::Owned(format!(r"{}{}{}\{}", prefix, prefix_sep, ELLIPSIS, tail));
+    }
+    // Then try dropping the prefix.
+    if let Some(tail) = fitting_tail(subpath, max_chars.saturating_sub(2)) {
+        return Cow::Owned(format!(r"{}\{}", ELLIPSIS, tail));
+    }
+
+    // Otherwise keep as much of the end of the path as possible.
+    let keep = max_chars - 1;
+    let start = path.char_indices().rev().take(keep).last().map_or(path.len(), |(i, _)| i);
+    Cow::Owned(format!("{}{}", ELLIPSIS, &path[start..]))
+}
+
+/// Returns the longest run of whole components at the end of `subpath` that's
+/// no more than `max_chars` long.
+fn fitting_tail(subpath: &str, max_chars: usize) -> Option<&str> {
+    let mut tail = None;
+    let mut tail_len = 0;
+    for (index, c) in subpath.char_indices().rev() {
+        if matches!(c, '\\' | '/') && tail_len > 0 {
+            tail = Some(&subpath[index + 1..]);
+        }
+        tail_len += 1;
+        if tail_len > max_chars {
+            break;
+        }
+    }
+    tail
+}
+
+/// Convert a path to the form that Windows shells such as Explorer display.
+///
+/// * The drive letter is upper-cased.
+/// * `/` separators are replaced with `\`.
+/// * A verbatim prefix is removed if the user path is guaranteed to refer to
+///   the same file (e.g. `\\?\C:\file` becomes `C:\file` but `\\?\C:\file.`
+///   is unchanged because the trailing `.` would be removed).
+/// * Device paths use the `\\.\` prefix.
+///
+/// The path is otherwise unchanged. Use [`clean_utf8`](super::clean_utf8) to
+/// also remove `.` and `..` components.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::to_shell_display;
+///
+/// assert_eq!(to_shell_display(r"\\?\c:\Users\file.txt"), r"C:\Users\file.txt");
+/// assert_eq!(to_shell_display("d:/projects/omnipath"), r"D:\projects\omnipath");
+/// assert_eq!(to_shell_display(r"\\?\UNC\server\share\file"), r"\\server\share\file");
+/// assert_eq!(to_shell_display("//?/pipe/name"), r"\\.\pipe\name");
+/// ```
+pub fn to_shell_display(path: &str) -> String {
+    fn upper_case_drive(path: &mut str) {
+        if matches!(path.as_bytes(), [drive, b':', ..] if drive.is_ascii_alphabetic()) {
+            path[..1].make_ascii_uppercase();
+        }
+    }
+
+    if is_verbatim_str(path) {
+        // The case of the drive letter doesn't matter so it can be changed
+        // before checking if the prefix is safe to remove.
+        let mut verbatim = String::from(path);
+        upper_case_drive(&mut verbatim[r"\\?\".len()..]);
+        return match user_prefix(&verbatim) {
+            UserPrefix::Replace { len, with } => {
+                verbatim.replace_range(..len, with);
+                verbatim
+            }
+            UserPrefix::Kept | UserPrefix::Lossy => String::from(path),
+        };
+    }
+    let (kind, rest) = WinPathKind::split_str(path);
+    let mut display = String::with_capacity(path.len());
+    kind.write_prefix(&mut display);
+    display.extend(rest.chars().map(|c| if c == '/' { '\\' } else { c }));
+    upper_case_drive(&mut display);
+    display
+}
+
+/// How to lexically convert a verbatim path to a user path.
+pub(crate) enum UserPrefix {
+    /// The path isn't verbatim.
+    Kept,
+    /// The path can't be converted without possibly changing which file it
+    /// refers to.
+    Lossy,
+    /// Replace the first `len` bytes of the path `with` a new prefix.
+    Replace { len: usize, with: &'static str },
+}
+
+/// Find how a verbatim path can be converted to a user path without using the
+/// OS.
+pub(crate) fn user_prefix(path: &str) -> UserPrefix {
+    let subpath = match Win32Absolute::from_verbatim_str(path) {
+        Ok(_) if verbatim_has_forward_slash(path) => return UserPrefix::Lossy,
+        Ok((Win32Absolute::Drive(_), subpath)) => subpath,
+        Ok((Win32Absolute::Unc, subpath)) => {
+            // `\\?\UNC\server\share` => `\\server\share`
+            let len = path.len() - subpath.len();
+            return match subpath.strip_prefix('\\') {
+                Some(unc) if !subpath_needs_normalization(WinPathKind::Unc, unc) => {
+                    UserPrefix::Replace { len, with: r"\" }
+                }
+                _ => UserPrefix::Lossy,
+            };
+        }
+        Ok((Win32Absolute::Device, _)) | Err(VerbatimParseError::EmptyPath) => {
+            return UserPrefix::Lossy
+        }
+        Err(VerbatimParseError::NotVerbatim) => return UserPrefix::Kept,
+    };
+    // `\\?\C:\` => `C:\`
+    let (kind, rest) = WinPathKind::split_str(subpath);
+    if subpath_needs_normalization(kind, rest) {
+        UserPrefix::Lossy
+    } else {
+        UserPrefix::Replace { len: path.len() - subpath.len(), with: "" }
+    }
+}
@@ -1,9 +1,16 @@
 // Temporary while this code is being fleshed out.
 #![allow(dead_code)]
+use alloc::borrow::Cow;
+use alloc::string::String;
+use core::fmt;
 use core::str;
 
+use super::name::is_reserved_device_name;
 use crate::util;
 
+/// The longest path, in UTF-16 code units, not including the null terminator.
+pub(crate) const MAX_PATH: usize = 259;
+
 // .:?/\a
 // &['\\', '/', '.', '?', ':', 'T', '£', '三', '😍']
 
@@ -15,20 +22,23 @@ pub struct ParsedUtf8Path<'a> {
 }
 impl<'a> ParsedUtf8Path<'a> {
     /// Parse a UTF-8 string into a prefix and subpath..
-    pub fn from_utf8(path: &'a str) -> ParsedUtf8Path<'a> {
+    pub const fn from_utf8(path: &'a str) -> ParsedUtf8Path<'a> {
         let (kind, len) = WinPathKind::from_str_with_len(path);
         Self {
             path,
             kind,
             prefix_len: match kind {
-                WinPathKind::Unc => len + str_unc_prefix_len(&path[len..]),
+                // SAFETY: The kind's prefix is always followed by a char boundary.
+                WinPathKind::Unc => {
+                    len + str_unc_prefix_len(unsafe { util::trim_start_str(path, len) })
+                }
                 _ => len,
             },
         }
     }
 
     /// Get the original, unparsed path.
-    pub fn as_utf8(&self) -> &str {
+    pub const fn as_utf8(&self) -> &str {
         self.path
     }
 
@@ -37,69 +47,139 @@ impl<'a> ParsedUtf8Path<'a> {
         self.kind
     }
 
-    /// Normalize the kind
+    /// Normalize the kind's prefix.
+    ///
+    /// Separators are converted to `\`. UNC paths only include the leading
+    /// `\\`, not the server and share names.
     pub fn normalized_str_kind(&self) -> NormalizedStrKind {
+        let mut buffer = [0; NormalizedStrKind::CAPACITY];
         match self.kind() {
-            WinPathKind::DriveRelative(_) => {
-                let mut buffer = [0; 4];
-                buffer[..self.prefix_len].copy_from_slice(&self.path.as_bytes()[..self.prefix_len]);
-                NormalizedStrKind { buffer, len: self.prefix_len }
+            WinPathKind::DriveRelative(_) | WinPathKind::Drive(_) => {
+                // The drive prefix is at most a three byte character followed by `:\`.
+                let prefix = match self.path.as_bytes().get(..self.prefix_len) {
+                    Some(prefix) if prefix.len() <= buffer.len() => prefix,
+                    _ => return NormalizedStrKind { buffer, len: 0, kind: self.kind() },
+                };
+                buffer[..prefix.len()].copy_from_slice(prefix);
+                if let (WinPathKind::Drive(_), Some(last)) =
+                    (self.kind(), buffer[..prefix.len()].last_mut())
+                {
+                    *last = b'\\';
+                }
+                NormalizedStrKind { buffer, len: prefix.len(), kind: self.kind() }
             }
-            WinPathKind::Drive(_) => {
-                let mut buffer = [0; 4];
-                buffer[..self.prefix_len].copy_from_slice(&self.path.as_bytes()[..self.prefix_len]);
-                buffer[self.prefix_len - 1] = b'\\';
-                NormalizedStrKind { buffer, len: self.prefix_len }
+            WinPathKind::Verbatim => {
+                buffer[..4].copy_from_slice(br"\\?\");
+                NormalizedStrKind { buffer, len: 4, kind: self.kind() }
             }
-            WinPathKind::Verbatim => NormalizedStrKind { buffer: *br"\\?\", len: 4 },
             WinPathKind::Device => {
-                // Preserves the `.` or `?`.
-                // Maybe we should normalize this as `.` even if that's not what the OS does.
-                let mut buffer = [b'\\'; 4];
-                buffer[2] = self.path.as_bytes()[2];
-                NormalizedStrKind { buffer, len: 4 }
+                // Preserves the `.` or `?`. Use `canonical` to always get `.`.
+                buffer[..4].copy_from_slice(br"\\.\");
+                if let Some(&b'?') = self.path.as_bytes().get(2) {
+                    buffer[2] = b'?';
+                }
+                NormalizedStrKind { buffer, len: 4, kind: self.kind() }
+            }
+            WinPathKind::CurrentDirectoryRelative => {
+                NormalizedStrKind { buffer, len: 0, kind: self.kind() }
+            }
+            WinPathKind::RootRelative => {
+                buffer[0] = b'\\';
+                NormalizedStrKind { buffer, len: 1, kind: self.kind() }
+            }
+            WinPathKind::Unc => {
+                buffer[..2].copy_from_slice(br"\\");
+                NormalizedStrKind { buffer, len: 2, kind: self.kind() }
             }
-            WinPathKind::CurrentDirectoryRelative => NormalizedStrKind { buffer: [0; 4], len: 0 },
-            WinPathKind::RootRelative => NormalizedStrKind { buffer: [b'\\', 0, 0, 0], len: 1 },
-            WinPathKind::Unc => NormalizedStrKind { buffer: [b'\\', b'\\', 0, 0], len: 2 },
         }
     }
 
+    /// Get the server name of a UNC or verbatim UNC path.
+    ///
+    /// Returns `None` for other kinds of path or if the server name is empty.
+    pub fn unc_server(&self) -> Option<&'a str> {
+        unc_server_share(self.path).map(|(server, _)| server)
+    }
+
+    /// Get the share name of a UNC or verbatim UNC path.
+    ///
+    /// Returns `None` for other kinds of path or if there is no server name or
+    /// share name (e.g. `\\server` or `\\server\\file`).
+    pub fn unc_share(&self) -> Option<&'a str> {
+        unc_server_share(self.path).and_then(|(_, share)| share)
+    }
+
     /// Returns the (prefix, subpath) pair.
-    pub fn parts<'b>(&'b self) -> (&'a str, &'a str)
+    pub const fn parts<'b>(&'b self) -> (&'a str, &'a str)
     where
         'a: 'b,
     {
-        self.path.split_at(self.prefix_len)
+        // SAFETY: The prefix always ends on a char boundary.
+        unsafe { util::split_str_at(self.path, self.prefix_len) }
     }
 }
 
+/// The prefix of a parsed path's kind with normalized separators.
+///
+/// A device path prefix can be written as either `\\.\` or `\\?\` using any
+/// mix of separators, other than the exact `\\?\` which is a verbatim path.
+/// Both `//./pipe/x` and `//?/pipe/x` are device paths. So there are two
+/// spellings of the normalized prefix:
+///
+/// * [`as_written`](Self::as_written) keeps the `.` or `?`, so `//?/pipe/x`
+///   gives `\\?\`. Cleaning a path uses this so it never changes the spelling
+///   the user chose.
+/// * [`canonical`](Self::canonical) always uses `\\.\`. This is the spelling
+///   used when a path is built from its parts, e.g. by
+///   [`WinPathKind::write_prefix`] and [`compose`].
+///
+/// Other kinds have only one spelling.
 pub struct NormalizedStrKind {
-    buffer: [u8; 4],
+    buffer: [u8; Self::CAPACITY],
     len: usize,
+    kind: WinPathKind,
 }
 impl NormalizedStrKind {
-    pub fn as_str(&self) -> &str {
-        str::from_utf8(&self.buffer[..self.len]).unwrap()
+    /// Enough for a drive prefix using a three byte character.
+    const CAPACITY: usize = 5;
+
+    /// The normalized prefix, preserving the `.` or `?` of a device path.
+    pub fn as_written(&self) -> &str {
+        self.buffer.get(..self.len).and_then(|s| str::from_utf8(s).ok()).unwrap_or("")
+    }
+
+    /// The normalized prefix, always using `\\.\` for a device path.
+    pub fn canonical(&self) -> &str {
+        match self.kind {
+            WinPathKind::Device => WinPathKind::DEVICE_PREFIX_STR,
+            _ => self.as_written(),
+        }
     }
 }
 
 /// Parse the server and share name from the path.
 ///
 /// This assumes the leading `\\` has already be parsed.
-fn str_unc_prefix_len(path: &str) -> usize {
-    let mut iter = path.as_bytes().iter();
-    match iter.position(|&c| c == b'\\' || c == b'/') {
-        Some(pos) => iter.position(|&c| c == b'\\' || c == b'/').map(|n| pos + n + 1),
-        None => None,
+const fn str_unc_prefix_len(path: &str) -> usize {
+    let bytes = path.as_bytes();
+    let mut separators = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if matches!(bytes[i], b'\\' | b'/') {
+            separators += 1;
+            if separators == 2 {
+                return i;
+            }
+        }
+        i += 1;
     }
-    .unwrap_or(path.len())
+    bytes.len()
 }
 
 /// Windows path type.
 ///
-/// This does not do any validation so parsing the kind will never fail,
-/// even for broken or invalid paths.
+/// This does not do any validation so parsing the kind will never fail or
+/// panic, even for broken or invalid paths.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum WinPathKind {
     /// A traditional drive path such as `C:\`, `R:\`, etc.
@@ -129,6 +209,15 @@ pub enum WinPathKind {
 }
 
 impl WinPathKind {
+    /// The prefix of a verbatim path.
+    pub const VERBATIM_PREFIX_STR: &'static str = r"\\?\";
+    /// The prefix of a verbatim UNC path.
+    pub const VERBATIM_UNC_PREFIX_STR: &'static str = r"\\?\UNC\";
+    /// The prefix of a device path.
+    pub const DEVICE_PREFIX_STR: &'static str = r"\\.\";
+    /// The prefix of a UNC path.
+    pub const UNC_PREFIX_STR: &'static str = r"\\";
+
     /// Split the path into `WinPathKind` and the rest of the path.
     ///
     /// Note that this only splits off the smallest part needed to identify the
@@ -143,9 +232,9 @@ impl WinPathKind {
 
     pub(crate) const fn from_str_with_len(path: &str) -> (Self, usize) {
         let kind = Self::from_str(path);
-        let len = match kind {
-            Self::Drive(_) | Self::DriveRelative(_) => {
-                kind.utf16_len() - 1 + (util::utf8_len(path.as_bytes()[0]) as usize)
+        let len = match (kind, path.as_bytes()) {
+            (Self::Drive(_) | Self::DriveRelative(_), [first, ..]) => {
+                kind.utf16_len() - 1 + (util::utf8_len(*first) as usize)
             }
             _ => kind.utf16_len(),
         };
@@ -155,10 +244,10 @@ impl WinPathKind {
     /// Examine the path prefix to find the type of the path given.
     pub const fn from_str(path: &str) -> Self {
         let bytes = path.as_bytes();
-
-        if bytes.is_empty() {
-            return WinPathKind::CurrentDirectoryRelative;
-        }
+        let first = match bytes {
+            [first, ..] => *first,
+            [] => return WinPathKind::CurrentDirectoryRelative,
+        };
 
         // If the path starts with `\\?\` then it's a verbatim path.
         // Note that this is an exact match. `//?/` is not a verbatim path.
@@ -169,7 +258,7 @@ impl WinPathKind {
             return Self::Verbatim;
         }
 
-        match util::utf8_len(bytes[0]) {
+        match util::utf8_len(first) {
             // If the first Unicode scalar would need more than one UTF-16 code unit
             // then this must be a relative path because it won't match any prefix.
             4.. => Self::CurrentDirectoryRelative,
@@ -177,7 +266,7 @@ impl WinPathKind {
             // Drive, DriveRelative or Relative.
             n @ 2.. => {
                 match_pattern! {
-                    util::trim_start(bytes, n);
+                    util::trim_start(bytes, n as usize);
                     [':', /, ..] => Self::Drive(util::bmp_utf8_to_utf16(bytes)),
                     [':', ..] => Self::DriveRelative(util::bmp_utf8_to_utf16(bytes)),
                     _ => Self::CurrentDirectoryRelative
@@ -195,15 +284,93 @@ impl WinPathKind {
                 // `\`
                 [/, ..] => Self::RootRelative,
                 // `C:\`
-                [_, ':', /, ..] => Self::Drive(bytes[0] as u16),
+                [_, ':', /, ..] => Self::Drive(first as u16),
                 // `C:`
-                [_, ':', ..] => Self::DriveRelative(bytes[0] as u16),
+                [_, ':', ..] => Self::DriveRelative(first as u16),
                 // Anything else
                 _ => Self::CurrentDirectoryRelative
             },
         }
     }
 
+    /// Examine the path prefix to find the type of the path given, without
+    /// requiring it to be valid Unicode.
+    ///
+    /// This agrees with [`WinPathKind::from_str`] for paths that are valid
+    /// Unicode. It only looks at the first few code units (on Windows) or
+    /// bytes (elsewhere) of the path and doesn't allocate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use omnipath::windows::WinPathKind;
+    /// use std::ffi::OsStr;
+    ///
+    /// assert_eq!(WinPathKind::from_os_str(OsStr::new(r"\\?\C:\file.txt")), WinPathKind::Verbatim);
+    /// assert_eq!(WinPathKind::from_os_str(OsStr::new(r"C:\file.txt")), WinPathKind::Drive(b'C' as u16));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_os_str(path: &std::ffi::OsStr) -> Self {
+        #[cfg(windows)]
+        {
+            use std::os::windows::ffi::OsStrExt;
+            // The longest prefix is four code units long.
+            let mut units = [0; 4];
+            let mut len = 0;
+            for (slot, unit) in units.iter_mut().zip(path.encode_wide()) {
+                *slot = unit;
+                len += 1;
+            }
+            Self::from_wide_prefix(&units[..len])
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            // The longest prefix is a three byte drive letter followed by `:\`.
+            // Only the valid UTF-8 at the start of the path is examined.
+            let bytes = path.as_bytes();
+            let bytes = &bytes[..bytes.len().min(8)];
+            let valid = match str::from_utf8(bytes) {
+                Ok(valid) => valid,
+                Err(e) => str::from_utf8(&bytes[..e.valid_up_to()]).unwrap_or_default(),
+            };
+            Self::from_str(valid)
+        }
+        #[cfg(not(any(windows, unix)))]
+        {
+            Self::from_str(&path.to_string_lossy())
+        }
+    }
+
+    /// Examine the first UTF-16 code units of a path to find its type.
+    ///
+    /// `units` should contain at least the first four code units, if the
+    /// path has that many. The order of these matches mirrors
+    /// [`WinPathKind::from_str`].
+    pub(crate) fn from_wide_prefix(units: &[u16]) -> Self {
+        const SEP: u16 = b'\\' as u16;
+        const SLASH: u16 = b'/' as u16;
+        const COLON: u16 = b':' as u16;
+        const QUERY: u16 = b'?' as u16;
+        const DOT: u16 = b'.' as u16;
+        const fn is_sep(unit: u16) -> bool {
+            matches!(unit, SEP | SLASH)
+        }
+        // Drives must be a single UTF-16 code unit.
+        const fn is_drive(unit: u16) -> bool {
+            !matches!(unit, 0xD800..=0xDFFF)
+        }
+        match *units {
+            [SEP, SEP, QUERY, SEP, ..] => Self::Verbatim,
+            [a, b, DOT | QUERY, c, ..] if is_sep(a) && is_sep(b) && is_sep(c) => Self::Device,
+            [a, b, ..] if is_sep(a) && is_sep(b) => Self::Unc,
+            [a, ..] if is_sep(a) => Self::RootRelative,
+            [drive, COLON, sep, ..] if is_drive(drive) && is_sep(sep) => Self::Drive(drive),
+            [drive, COLON, ..] if is_drive(drive) => Self::DriveRelative(drive),
+            _ => Self::CurrentDirectoryRelative,
+        }
+    }
+
     /// Is the path absolute. Being absolute means it doesn't need to be joined
     /// to a base path (e.g. the current directory, or a drive current directory)
     pub const fn is_absolute(self) -> bool {
@@ -240,10 +407,10 @@ impl WinPathKind {
     /// The number of UTF-8 code units that make up the path kind.
     pub const fn utf8_len(self) -> usize {
         const fn drive_utf8_len(drive: u16) -> usize {
-            if drive > 0x7F {
-                2
-            } else {
-                1
+            match drive {
+                0..=0x7F => 1,
+                0x80..=0x7FF => 2,
+                _ => 3,
             }
         }
         match self {
@@ -256,8 +423,192 @@ impl WinPathKind {
             Self::RootRelative => r"\".len(),
         }
     }
+
+    /// Append the canonical prefix for this kind of path to a `String`.
+    ///
+    /// Drive paths include the trailing `\` and device paths always use the
+    /// `\\.\` spelling. UNC paths only write the leading `\\`; use
+    /// [`compose`] to also write the server and share names.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use omnipath::windows::WinPathKind;
+    ///
+    /// let mut path = String::new();
+    /// WinPathKind::Drive(b'C' as u16).write_prefix(&mut path);
+    /// path.push_str("file.txt");
+    /// assert_eq!(path, r"C:\file.txt");
+    /// ```
+    pub fn write_prefix(self, out: &mut String) {
+        // Writing to a `String` can't fail.
+        let _ = self.write_prefix_fmt(out);
+    }
+
+    /// Write the canonical prefix for this kind of path to a [`fmt::Write`].
+    ///
+    /// See [`WinPathKind::write_prefix`].
+    pub fn write_prefix_fmt<W: fmt::Write + ?Sized>(self, out: &mut W) -> fmt::Result {
+        fn drive_char(drive: u16) -> char {
+            char::from_u32(drive.into()).unwrap_or(char::REPLACEMENT_CHARACTER)
+        }
+        match self {
+            Self::Drive(drive) => {
+                out.write_char(drive_char(drive))?;
+                out.write_str(r":\")
+            }
+            Self::DriveRelative(drive) => {
+                out.write_char(drive_char(drive))?;
+                out.write_char(':')
+            }
+            Self::Unc => out.write_str(Self::UNC_PREFIX_STR),
+            // The same as `NormalizedStrKind::canonical`.
+            Self::Device => out.write_str(Self::DEVICE_PREFIX_STR),
+            Self::Verbatim => out.write_str(Self::VERBATIM_PREFIX_STR),
+            Self::RootRelative => out.write_char('\\'),
+            Self::CurrentDirectoryRelative => Ok(()),
+        }
+    }
+}
+
+/// Get the root directory of a drive, e.g. `C:\`.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::drive_root;
+///
+/// assert_eq!(drive_root('C'), r"C:\");
+/// ```
+pub fn drive_root(drive: char) -> String {
+    let mut root = String::with_capacity(drive.len_utf8() + r":\".len());
+    root.push(drive);
+    root.push_str(r":\");
+    root
+}
+
+/// Turn a bare drive (e.g. `C:`) into the root of the drive (e.g. `C:\`).
+///
+/// Note that this changes the meaning of the path. On Windows `C:` is a drive
+/// relative path that refers to the current directory of the `C:` drive,
+/// which may not be the root. Use this when the user intends a bare drive to
+/// mean the whole drive. Any other path is returned unchanged.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::ensure_drive_root;
+///
+/// assert_eq!(ensure_drive_root("C:"), r"C:\");
+/// assert_eq!(ensure_drive_root(r"C:\"), r"C:\");
+/// // A drive relative path with a file name is left alone.
+/// assert_eq!(ensure_drive_root("C:foo"), "C:foo");
+/// ```
+pub fn ensure_drive_root(path: &str) -> Cow<'_, str> {
+    match WinPathKind::split_str(path) {
+        (WinPathKind::DriveRelative(_), "") => {
+            let mut root = String::with_capacity(path.len() + 1);
+            root.push_str(path);
+            root.push('\\');
+            Cow::Owned(root)
+        }
+        _ => Cow::Borrowed(path),
+    }
 }
 
+/// Build a path string from its kind and components.
+///
+/// This is the inverse of parsing a path's [`WinPathKind`]. `unc` is the
+/// server and share name and must be given for [`WinPathKind::Unc`] paths and
+/// only for UNC paths. Components are joined using `\`.
+///
+/// An error is returned if a component, server or share name is empty or
+/// contains a separator, or if the resulting path would be parsed as a
+/// different kind (e.g. a relative path whose first component is `C:`).
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::{compose, WinPathKind};
+///
+/// assert_eq!(
+///     compose(WinPathKind::Unc, Some(("server", "share")), &["dir", "file.txt"]).unwrap(),
+///     r"\\server\share\dir\file.txt"
+/// );
+/// assert_eq!(
+///     compose(WinPathKind::Drive(b'C' as u16), None, &["dir", "file.txt"]).unwrap(),
+///     r"C:\dir\file.txt"
+/// );
+/// assert!(compose(WinPathKind::Unc, None, &["file.txt"]).is_err());
+/// assert!(compose(WinPathKind::CurrentDirectoryRelative, None, &["C:", "file.txt"]).is_err());
+/// ```
+pub fn compose(
+    kind: WinPathKind,
+    unc: Option<(&str, &str)>,
+    components: &[&str],
+) -> Result<String, ComposeError> {
+    fn is_valid(component: &str) -> bool {
+        !component.is_empty() && !component.contains(['\\', '/'])
+    }
+
+    let mut path = String::new();
+    kind.write_prefix(&mut path);
+    match (kind, unc) {
+        (WinPathKind::Unc, Some((server, share))) => {
+            if !is_valid(server) || !is_valid(share) {
+                return Err(ComposeError::InvalidComponent);
+            }
+            path.push_str(server);
+            path.push('\\');
+            path.push_str(share);
+            if !components.is_empty() {
+                path.push('\\');
+            }
+        }
+        (WinPathKind::Unc, None) => return Err(ComposeError::MissingServerShare),
+        (_, Some(_)) => return Err(ComposeError::UnexpectedServerShare),
+        (_, None) => {}
+    }
+    for (index, component) in components.iter().enumerate() {
+        if !is_valid(component) {
+            return Err(ComposeError::InvalidComponent);
+        }
+        if index != 0 {
+            path.push('\\');
+        }
+        path.push_str(component);
+    }
+    if WinPathKind::from_str(&path) != kind {
+        return Err(ComposeError::KindMismatch);
+    }
+    Ok(path)
+}
+
+/// The error returned by [`compose`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComposeError {
+    /// A UNC path was given without a server and share name.
+    MissingServerShare,
+    /// A server and share name was given for a path that is not UNC.
+    UnexpectedServerShare,
+    /// A component, server or share name is empty or contains a separator.
+    InvalidComponent,
+    /// The composed path would be parsed as a different kind of path.
+    KindMismatch,
+}
+impl fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::MissingServerShare => "a UNC path needs a server and share name",
+            Self::UnexpectedServerShare => "only UNC paths can have a server and share name",
+            Self::InvalidComponent => "path components must not be empty or contain separators",
+            Self::KindMismatch => "the composed path has a different kind",
+        })
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for ComposeError {}
+
 /// The type of relative path.
 #[derive(Debug, Clone, Copy)]
 pub enum Win32Relative {
@@ -286,10 +637,13 @@ impl Win32Relative {
 }
 
 /// The type of non-verbatim absolute path.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Win32Absolute {
+    /// A drive path such as `C:\`, with the drive letter as a UTF-16 code unit.
     Drive(u16),
+    /// A UNC path such as `\\server\share`.
     Unc,
+    /// A device path such as `\\.\pipe\name`.
     Device,
 }
 impl Win32Absolute {
@@ -303,50 +657,476 @@ impl Win32Absolute {
         }
     }
 
-    /// Get the Win32 type of a verbatim path.
-    pub(crate) const fn from_verbatim_str(path: &str) -> Result<(Self, &str), ()> {
-        let verbatim = match VerbatimStr::new(path) {
-            Ok(verbatim) => verbatim,
-            Err(e) => return Err(e),
-        };
-        let kind = verbatim.win32_kind();
-        let rest = match kind {
-            Win32Absolute::Unc => unsafe { util::trim_start_str(verbatim.path, "UNC".len()) },
-            _ => verbatim.path,
+    /// Get the Win32 type of a verbatim path and the rest of the path.
+    ///
+    /// This is the same as [`VerbatimStr::new`] followed by
+    /// [`VerbatimStr::win32_parts`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use omnipath::windows::{VerbatimParseError, Win32Absolute};
+    ///
+    /// assert_eq!(
+    ///     Win32Absolute::from_verbatim_str(r"\\?\UNC\server\share"),
+    ///     Ok((Win32Absolute::Unc, r"\server\share"))
+    /// );
+    /// assert_eq!(
+    ///     Win32Absolute::from_verbatim_str(r"C:\file.txt"),
+    ///     Err(VerbatimParseError::NotVerbatim)
+    /// );
+    /// ```
+    pub const fn from_verbatim_str(path: &str) -> Result<(Self, &str), VerbatimParseError> {
+        match VerbatimStr::new(path) {
+            Ok(verbatim) => Ok(verbatim.win32_parts()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the Win32 type of a verbatim path encoded as UTF-16.
+    ///
+    /// This is the same as [`Win32Absolute::from_verbatim_str`] except that it
+    /// also works for paths that are not valid Unicode.
+    #[cfg(feature = "std")]
+    pub(crate) fn from_verbatim_wide(path: &[u16]) -> Result<(Self, &[u16]), VerbatimParseError> {
+        const SEP: u16 = b'\\' as u16;
+        const COLON: u16 = b':' as u16;
+        const QUERY: u16 = b'?' as u16;
+        // `UNC` ignoring ASCII case.
+        fn is_unc(u: u16, n: u16, c: u16) -> bool {
+            let lower = |w: u16| w | 0x20;
+            lower(u) == u16::from(b'u')
+                && lower(n) == u16::from(b'n')
+                && lower(c) == u16::from(b'c')
+        }
+        let rest = match *path {
+            [SEP, SEP, QUERY, SEP] => return Err(VerbatimParseError::EmptyPath),
+            [SEP, SEP, QUERY, SEP, ..] => &path[4..],
+            _ => return Err(VerbatimParseError::NotVerbatim),
         };
-        Ok((kind, rest))
+        match *rest {
+            [u, n, c, SEP, ..] | [u, n, c] if is_unc(u, n, c) => Ok((Self::Unc, &rest[3..])),
+            // The drive must be a single UTF-16 code unit.
+            [drive, COLON, SEP, ..] if !matches!(drive, 0xD800..=0xDFFF) => {
+                Ok((Self::Drive(drive), rest))
+            }
+            _ => Ok((Self::Device, rest)),
+        }
     }
 }
 
+/// A parsed verbatim path.
+///
+/// This can be used to convert a verbatim path to a user path using your own
+/// rules. The subpath is everything after the `\\?\` prefix.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::{VerbatimParseError, VerbatimStr, Win32Absolute};
+///
+/// let verbatim = VerbatimStr::new(r"\\?\C:\file.txt").unwrap();
+/// assert_eq!(verbatim.subpath(), r"C:\file.txt");
+/// assert_eq!(verbatim.win32_kind(), Win32Absolute::Drive(b'C' as u16));
+///
+/// let verbatim = VerbatimStr::new(r"\\?\UNC\server\share").unwrap();
+/// assert_eq!(verbatim.win32_parts(), (Win32Absolute::Unc, r"\server\share"));
+///
+/// assert_eq!(VerbatimStr::new(r"C:\file.txt").err(), Some(VerbatimParseError::NotVerbatim));
+/// assert_eq!(VerbatimStr::new(r"\\?\").err(), Some(VerbatimParseError::EmptyPath));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct VerbatimStr<'a> {
     path: &'a str,
 }
 impl<'a> VerbatimStr<'a> {
-    const fn new(path: &'a str) -> Result<Self, ()> {
+    /// Parse a verbatim path.
+    ///
+    /// An error is returned if the path doesn't start with `\\?\` or if there
+    /// is nothing after the prefix.
+    pub const fn new(path: &'a str) -> Result<Self, VerbatimParseError> {
         match WinPathKind::split_str(path) {
+            (WinPathKind::Verbatim, rest) if rest.is_empty() => Err(VerbatimParseError::EmptyPath),
             (WinPathKind::Verbatim, rest) => Ok(Self { path: rest }),
-            _ => Err(()),
+            _ => Err(VerbatimParseError::NotVerbatim),
         }
     }
-    const fn win32_kind(&self) -> Win32Absolute {
+
+    /// The path after the `\\?\` prefix.
+    pub const fn subpath(&self) -> &'a str {
+        self.path
+    }
+
+    /// The Win32 kind of path and the rest of the path.
+    ///
+    /// For UNC paths the `UNC` is removed from the subpath, leaving the `\`
+    /// before the server name. Otherwise the rest is the same as
+    /// [`subpath`](Self::subpath).
+    pub const fn win32_parts(&self) -> (Win32Absolute, &'a str) {
+        let kind = self.win32_kind();
+        let rest = match kind {
+            // SAFETY: `UNC` is ASCII so this is a char boundary.
+            Win32Absolute::Unc => unsafe { util::trim_start_str(self.path, "UNC".len()) },
+            _ => self.path,
+        };
+        (kind, rest)
+    }
+
+    /// The type of path the subpath would be as a Win32 path.
+    ///
+    /// `UNC` (in any case) is a UNC path and a drive followed by `\` is a
+    /// drive path. Anything else, including a drive without a `\` (e.g.
+    /// `\\?\C:`), is a device path.
+    pub const fn win32_kind(&self) -> Win32Absolute {
         // C:\, \\.\, \\
-        match self.path.as_bytes() {
+        let bytes = self.path.as_bytes();
+        let first = match_pattern! {
+            bytes;
             // UNC\
-            // Canonically `UNC` is uppercase but maybe we should ignore case here.
-            [b'U', b'N', b'C', b'\\', ..] | [b'U', b'N', b'C'] => Win32Absolute::Unc,
-            // C:\
-            [d, b':', b'\\', ..] | [d, b':'] => Win32Absolute::Drive(*d as u16),
-            [d1, d2, b':', b'\\', ..] | [d1, d2, b':'] => {
-                let drive = util::bmp_utf8_to_utf16(&[*d1, *d2]);
-                Win32Absolute::Drive(drive)
+            // Canonically `UNC` is uppercase but Windows ignores case.
+            [U, N, C, b'\\', ..] | [U, N, C] => return Win32Absolute::Unc,
+            [first, ..] => *first,
+            _ => return Win32Absolute::Device
+        };
+        // C:\
+        // The drive must be a single character that fits in one UTF-16 code unit.
+        let drive_len = util::utf8_len(first);
+        match util::trim_start(bytes, drive_len as usize) {
+            [b':', b'\\', ..] if drive_len <= 3 => {
+                Win32Absolute::Drive(util::bmp_utf8_to_utf16(bytes))
             }
+            // A drive without a root (e.g. `\\?\C:`) is the volume device
+            // rather than a directory.
             // Anything else is used as a device path.
             _ => Win32Absolute::Device,
         }
     }
+
+    /// The kind of verbatim path, including the kind of some device paths.
+    ///
+    /// See [`WinVerbatimKind`] for how device paths are classified.
+    pub fn verbatim_kind(&self) -> WinVerbatimKind {
+        match self.win32_kind() {
+            Win32Absolute::Drive(drive) => WinVerbatimKind::Drive(drive),
+            Win32Absolute::Unc => WinVerbatimKind::Unc,
+            Win32Absolute::Device => {
+                // Only `\` is a separator in verbatim paths.
+                let name = self.path.split('\\').next().unwrap_or_default();
+                WinVerbatimKind::from_device_name(name)
+            }
+        }
+    }
+}
+
+/// The error returned when parsing a verbatim path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerbatimParseError {
+    /// The path doesn't start with the `\\?\` verbatim prefix.
+    NotVerbatim,
+    /// There is nothing after the verbatim prefix.
+    EmptyPath,
+}
+impl fmt::Display for VerbatimParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotVerbatim => "the path is not a verbatim path",
+            Self::EmptyPath => "the verbatim path is empty after the prefix",
+        })
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for VerbatimParseError {}
+
+/// The kind of a verbatim path.
+///
+/// Verbatim paths that aren't drive or UNC paths are device paths. Some
+/// devices are identified by their name, which is the first component after
+/// the `\\?\` prefix. Names are compared ignoring ASCII case.
+///
+/// This is returned by [`VerbatimStr::verbatim_kind`].
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::{VerbatimStr, WinVerbatimKind};
+///
+/// let kind = |path| VerbatimStr::new(path).unwrap().verbatim_kind();
+/// assert_eq!(kind(r"\\?\C:\file.txt"), WinVerbatimKind::Drive(b'C' as u16));
+/// assert_eq!(kind(r"\\?\UNC\server\share"), WinVerbatimKind::Unc);
+/// assert_eq!(
+///     kind(r"\\?\Volume{26a21bda-a627-11d7-9931-806e6f6e6963}\dir\file"),
+///     WinVerbatimKind::VolumeGuid
+/// );
+/// assert_eq!(kind(r"\\?\GLOBALROOT\Device\HarddiskVolume3"), WinVerbatimKind::GlobalRoot);
+/// assert_eq!(kind(r"\\?\pipe\name"), WinVerbatimKind::Pipe);
+/// assert_eq!(kind(r"\\?\C:"), WinVerbatimKind::Device);
+/// assert_eq!(kind(r"\\?\HarddiskVolume3\file"), WinVerbatimKind::Other);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WinVerbatimKind {
+    /// A drive path such as `\\?\C:\`, with the drive letter as a UTF-16 code
+    /// unit.
+    Drive(u16),
+    /// A UNC path such as `\\?\UNC\server\share`.
+    Unc,
+    /// A drive without a root (e.g. `\\?\C:`), which is the volume itself, or
+    /// a DOS device name such as `\\?\NUL`.
+    Device,
+    /// A volume GUID path such as
+    /// `\\?\Volume{26a21bda-a627-11d7-9931-806e6f6e6963}\`.
+    VolumeGuid,
+    /// A path in the NT object namespace such as
+    /// `\\?\GLOBALROOT\Device\HarddiskVolume3\`.
+    GlobalRoot,
+    /// A named pipe such as `\\?\pipe\name`.
+    Pipe,
+    /// Any other device path.
+    Other,
+}
+impl WinVerbatimKind {
+    /// Classify a device path by its device name.
+    fn from_device_name(name: &str) -> Self {
+        let mut chars = name.chars();
+        if let (Some(_), Some(':'), None) = (chars.next(), chars.next(), chars.next()) {
+            Self::Device
+        } else if name.eq_ignore_ascii_case("GLOBALROOT") {
+            Self::GlobalRoot
+        } else if name.eq_ignore_ascii_case("pipe") {
+            Self::Pipe
+        } else if is_volume_guid(name) {
+            Self::VolumeGuid
+        } else if is_reserved_device_name(name) && !name.contains(['.', ':', ' ']) {
+            Self::Device
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Get the kind of a verbatim path encoded as UTF-16.
+    ///
+    /// Returns `None` if the path isn't verbatim. `\\?\` on its own is
+    /// [`Other`](Self::Other).
+    #[cfg(feature = "std")]
+    pub(crate) fn from_verbatim_wide(path: &[u16]) -> Option<Self> {
+        match Win32Absolute::from_verbatim_wide(path) {
+            Ok((Win32Absolute::Drive(drive), _)) => Some(Self::Drive(drive)),
+            Ok((Win32Absolute::Unc, _)) => Some(Self::Unc),
+            Ok((Win32Absolute::Device, subpath)) => {
+                let name = subpath.split(|&w| w == u16::from(b'\\')).next().unwrap_or_default();
+                // Device names that are not valid Unicode are not recognized.
+                match String::from_utf16(name) {
+                    Ok(name) => Some(Self::from_device_name(&name)),
+                    Err(_) => Some(Self::Other),
+                }
+            }
+            Err(VerbatimParseError::EmptyPath) => Some(Self::Other),
+            Err(VerbatimParseError::NotVerbatim) => None,
+        }
+    }
+}
+
+/// Test if a device name is `Volume{GUID}`.
+fn is_volume_guid(name: &str) -> bool {
+    const PREFIX: &str = "Volume{";
+    let guid = match name.get(..PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(PREFIX) => &name[PREFIX.len()..],
+        _ => return false,
+    };
+    let guid = match guid.strip_suffix('}') {
+        Some(guid) => guid.as_bytes(),
+        None => return false,
+    };
+    // For example, `26a21bda-a627-11d7-9931-806e6f6e6963`.
+    guid.len() == 36
+        && guid.iter().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => *b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
 }
 
 #[inline]
 pub const fn is_verbatim_str(path: &str) -> bool {
     matches!(path.as_bytes(), [b'\\', b'\\', b'?', b'\\', ..])
 }
+
+/// Get the server and share names from a UNC or verbatim UNC path.
+///
+/// Returns `None` if the path is not UNC or if the server or share name is
+/// missing or empty.
+pub(crate) fn unc_parts(path: &str) -> Option<(&str, &str)> {
+    match unc_server_share(path)? {
+        (server, Some(share)) => Some((server, share)),
+        _ => None,
+    }
+}
+
+/// Get the server name and, if there is one, the share name from a UNC or
+/// verbatim UNC path.
+///
+/// Returns `None` if the path is not UNC or the server name is empty. An empty
+/// share name is `None`.
+fn unc_server_share(path: &str) -> Option<(&str, Option<&str>)> {
+    let (rest, separators): (&str, &[char]) = match WinPathKind::split_str(path) {
+        (WinPathKind::Unc, rest) => (rest, &['\\', '/']),
+        (WinPathKind::Verbatim, _) => match Win32Absolute::from_verbatim_str(path) {
+            // Only `\` is a separator in verbatim paths.
+            Ok((Win32Absolute::Unc, rest)) => (rest.strip_prefix('\\')?, &['\\']),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let mut parts = rest.split(separators);
+    match (parts.next(), parts.next()) {
+        (Some(server), share) if !server.is_empty() => {
+            Some((server, share.filter(|share| !share.is_empty())))
+        }
+        _ => None,
+    }
+}
+
+/// Test if two paths are on the same UNC share.
+///
+/// Both UNC (`\\server\share`) and verbatim UNC (`\\?\UNC\server\share`)
+/// paths are supported. The server and share names are compared ignoring ASCII
+/// case. Returns `false` if either path is not a UNC path.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::same_unc_share;
+///
+/// assert!(same_unc_share(r"\\server\share\a.txt", r"\\SERVER\Share\dir\b.txt"));
+/// assert!(same_unc_share(r"\\server\share\a.txt", r"\\?\UNC\server\share\b.txt"));
+/// assert!(!same_unc_share(r"\\server\share\a.txt", r"\\server\other\a.txt"));
+/// assert!(!same_unc_share(r"\\server\share\a.txt", r"C:\share\a.txt"));
+/// ```
+pub fn same_unc_share(a: &str, b: &str) -> bool {
+    match (unc_parts(a), unc_parts(b)) {
+        (Some((server_a, share_a)), Some((server_b, share_b))) => {
+            server_a.eq_ignore_ascii_case(server_b) && share_a.eq_ignore_ascii_case(share_b)
+        }
+        _ => false,
+    }
+}
+
+/// The device referred to by a device path.
+///
+/// This is returned by [`device_path_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind<'a> {
+    /// A drive such as `\\.\C:`.
+    Drive(char),
+    /// Any other named device, such as `pipe` in `\\.\pipe\name`.
+    Named(&'a str),
+}
+
+/// Find the device that a device (`\\.\`) or verbatim (`\\?\`) path refers to.
+///
+/// The device is the first component after the prefix. A component made of a
+/// single character followed by `:` is a drive. Any other component is a named
+/// device. Note that this includes the `UNC` device used by verbatim UNC paths
+/// (e.g. `\\?\UNC\server\share`).
+///
+/// Returns `None` if the path is not a device or verbatim path or if the device
+/// name is empty.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::{device_path_kind, DeviceKind};
+///
+/// assert_eq!(device_path_kind(r"\\.\pipe\name"), Some(DeviceKind::Named("pipe")));
+/// assert_eq!(device_path_kind(r"\\.\C:\file.txt"), Some(DeviceKind::Drive('C')));
+/// assert_eq!(device_path_kind(r"\\?\C:\file.txt"), Some(DeviceKind::Drive('C')));
+/// assert_eq!(
+///     device_path_kind(r"\\?\GLOBALROOT\Device\HarddiskVolume1"),
+///     Some(DeviceKind::Named("GLOBALROOT"))
+/// );
+/// assert_eq!(device_path_kind(r"C:\file.txt"), None);
+/// ```
+pub fn device_path_kind(path: &str) -> Option<DeviceKind<'_>> {
+    let (rest, separators): (&str, &[char]) = match WinPathKind::split_str(path) {
+        (WinPathKind::Device, rest) => (rest, &['\\', '/']),
+        // Only `\` is a separator in verbatim paths.
+        (WinPathKind::Verbatim, rest) => (rest, &['\\']),
+        _ => return None,
+    };
+    let name = rest.split(separators).next().unwrap_or_default();
+    let mut chars = name.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (None, ..) => None,
+        (Some(drive), Some(':'), None) => Some(DeviceKind::Drive(drive)),
+        _ => Some(DeviceKind::Named(name)),
+    }
+}
+
+/// Test if a verbatim path contains a `/`.
+///
+/// Verbatim paths are passed to the kernel without being normalized so a `/`
+/// is not a path separator. Instead it becomes a literal part of a file name,
+/// which is almost never what was intended. Paths that are not verbatim always
+/// return `false`.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::verbatim_has_forward_slash;
+///
+/// assert!(verbatim_has_forward_slash(r"\\?\C:/path/to/file"));
+/// assert!(!verbatim_has_forward_slash(r"\\?\C:\path\to\file"));
+/// // `/` is a normal separator in non-verbatim paths.
+/// assert!(!verbatim_has_forward_slash(r"C:/path/to/file"));
+/// ```
+pub const fn verbatim_has_forward_slash(path: &str) -> bool {
+    if !is_verbatim_str(path) {
+        return false;
+    }
+    let bytes = path.as_bytes();
+    let mut i = r"\\?\".len();
+    while i < bytes.len() {
+        if bytes[i] == b'/' {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Test if a path uses both `\` and `/` as separators.
+///
+/// Both are separators in Windows paths but mixing them is usually a sign
+/// that a path was built from parts with different conventions. This is meant
+/// for lints that warn about such paths. Verbatim paths always return `false`
+/// because a `/` in a verbatim path isn't a separator (see
+/// [`verbatim_has_forward_slash`]).
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::has_mixed_separators;
+///
+/// assert!(has_mixed_separators(r"C:\path/to\file"));
+/// assert!(!has_mixed_separators(r"C:\path\to\file"));
+/// assert!(!has_mixed_separators("C:/path/to/file"));
+/// assert!(!has_mixed_separators(r"\\?\C:\path/to\file"));
+/// ```
+pub const fn has_mixed_separators(path: &str) -> bool {
+    if is_verbatim_str(path) {
+        return false;
+    }
+    let bytes = path.as_bytes();
+    let (mut backslash, mut slash) = (false, false);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => backslash = true,
+            b'/' => slash = true,
+            _ => {}
+        }
+        if backslash && slash {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
@@ -0,0 +1,87 @@
+//! Helpers for `PATH`-like lists of paths.
+
+#[cfg(feature = "std")]
+use std::{ffi::OsString, path::Path};
+
+/// The separator used between paths in a `PATH`-like list on Windows.
+pub const PATH_LIST_SEPARATOR: char = ';';
+
+/// Split a Windows `PATH`-like list into its entries.
+///
+/// Entries are separated by `;`. A `;` inside double quotes does not separate
+/// entries. If an entry is wrapped in double quotes then the quotes are
+/// removed. Any other quotes are left as they are. Empty entries are
+/// returned as empty strings.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::split_path_list;
+///
+/// let entries: Vec<&str> = split_path_list(r#"C:\Windows;"C:\Program Files\a;b";C:\bin"#).collect();
+/// assert_eq!(entries, [r"C:\Windows", r"C:\Program Files\a;b", r"C:\bin"]);
+/// ```
+pub fn split_path_list(list: &str) -> impl Iterator<Item = &str> + '_ {
+    let mut rest = Some(list);
+    core::iter::from_fn(move || {
+        let list = rest?;
+        let mut in_quotes = false;
+        let end = list.char_indices().find(|&(_, c)| {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            c == PATH_LIST_SEPARATOR && !in_quotes
+        });
+        let entry = match end {
+            Some((index, _)) => {
+                rest = Some(&list[index + 1..]);
+                &list[..index]
+            }
+            None => {
+                rest = None;
+                list
+            }
+        };
+        Some(entry.strip_prefix('"').and_then(|e| e.strip_suffix('"')).unwrap_or(entry))
+    })
+}
+
+/// Join paths into a Windows `PATH`-like list.
+///
+/// Entries are separated by `;`. An entry that contains a `;` is wrapped in
+/// double quotes so that [`split_path_list`] will return it unchanged.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::{join_path_list, split_path_list};
+///
+/// let entries = [r"C:\Windows", r"C:\Program Files\a;b"];
+/// let list = join_path_list(entries);
+/// assert_eq!(list, r#"C:\Windows;"C:\Program Files\a;b""#);
+///
+/// let list = list.to_str().unwrap();
+/// assert_eq!(split_path_list(list).collect::<Vec<_>>(), entries);
+/// ```
+#[cfg(feature = "std")]
+pub fn join_path_list<I, P>(paths: I) -> OsString
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut list = OsString::new();
+    for (index, path) in paths.into_iter().enumerate() {
+        let path = path.as_ref().as_os_str();
+        if index != 0 {
+            list.push(";");
+        }
+        if path.to_string_lossy().contains(PATH_LIST_SEPARATOR) {
+            list.push("\"");
+            list.push(path);
+            list.push("\"");
+        } else {
+            list.push(path);
+        }
+    }
+    list
+}
@@ -0,0 +1,177 @@
+//! Helpers for examining and validating individual file names.
+
+use alloc::string::String;
+
+#[cfg(feature = "std")]
+use super::kind::WinPathKind;
+
+/// Test if a file name looks like an 8.3 short name, such as `PROGRA~1`.
+///
+/// This is only a heuristic. It does not access the filesystem so it can't
+/// tell if the name really is a short name. Short names are made from an
+/// uppercase base name of up to eight characters that ends with a `~`
+/// followed by a number, and an optional extension of up to three
+/// characters.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::looks_like_short_name;
+///
+/// assert!(looks_like_short_name("PROGRA~1"));
+/// assert!(looks_like_short_name("PROGRA~1.TXT"));
+/// assert!(!looks_like_short_name("Program Files"));
+/// assert!(!looks_like_short_name("progra~1"));
+/// ```
+pub fn looks_like_short_name(name: &str) -> bool {
+    let (base, ext) = match name.split_once('.') {
+        Some((base, ext)) => (base, Some(ext)),
+        None => (name, None),
+    };
+    if let Some(ext) = ext {
+        if ext.is_empty() || ext.len() > 3 || !ext.bytes().all(is_short_name_byte) {
+            return false;
+        }
+    }
+    if base.len() > 8 {
+        return false;
+    }
+    let (stem, number) = match base.rsplit_once('~') {
+        Some(parts) => parts,
+        None => return false,
+    };
+    !stem.is_empty()
+        && stem.bytes().all(is_short_name_byte)
+        && matches!(number.as_bytes(), [b'1'..=b'9', ..])
+        && number.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Bytes that may appear in a generated short name.
+fn is_short_name_byte(b: u8) -> bool {
+    b.is_ascii_uppercase() || b.is_ascii_digit() || b"!#$%&'()-@^_`{}~".contains(&b)
+}
+
+/// Make a string into a valid file name by replacing anything Windows doesn't
+/// allow.
+///
+/// The following rules are applied:
+///
+/// * The reserved characters `<>:"/\|?*` and control characters (`U+0000` to
+///   `U+001F`) are replaced with `replacement`.
+/// * Trailing dots and spaces are replaced with `replacement` because Windows
+///   would otherwise remove them.
+/// * An empty name becomes `replacement`.
+/// * If the name would be redirected to a DOS device (e.g. `NUL` or
+///   `com1.txt`) then an `_` is added to the end of the name, before any
+///   extension.
+///
+/// If `replacement` is itself not allowed in a file name (or is a dot or
+/// space) then `_` is used instead.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::sanitize_component;
+///
+/// assert_eq!(sanitize_component("What? A <title>", '_'), "What_ A _title_");
+/// assert_eq!(sanitize_component("file.", '-'), "file-");
+/// assert_eq!(sanitize_component("nul.txt", '_'), "nul_.txt");
+/// ```
+pub fn sanitize_component(name: &str, replacement: char) -> String {
+    let replacement = match replacement {
+        '.' | ' ' => '_',
+        c if is_reserved_char(c) => '_',
+        c => c,
+    };
+    let trimmed = name.trim_end_matches(['.', ' ']);
+    let mut sanitized = String::with_capacity(name.len() + 1);
+    sanitized.extend(trimmed.chars().map(|c| if is_reserved_char(c) { replacement } else { c }));
+    sanitized.extend(name[trimmed.len()..].chars().map(|_| replacement));
+    if sanitized.is_empty() {
+        sanitized.push(replacement);
+    }
+    if is_reserved_device_name(&sanitized) {
+        let stem_len = sanitized.find('.').unwrap_or(sanitized.len());
+        sanitized.insert(stem_len, '_');
+    }
+    sanitized
+}
+
+/// Characters that can never be used in a file name.
+pub(crate) fn is_reserved_char(c: char) -> bool {
+    matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' | '\0'..='\x1F')
+}
+
+/// Test if a file name is redirected to a DOS device (e.g. `NUL` or `COM1`).
+///
+/// Windows opens the device instead of a file when given one of these names,
+/// even if it has an extension or is in a directory. The rules are:
+///
+/// * Only the stem is checked. This is everything before the first `.` (or
+///   `:`, which starts an alternate data stream). Trailing spaces in the stem
+///   are ignored.
+/// * The comparison ignores ASCII case.
+/// * The reserved stems are `CON`, `PRN`, `AUX`, `NUL`, `COM1` to `COM9` and
+///   `LPT1` to `LPT9`. The superscript digits `¹`, `²` and `³` can also be used
+///   with `COM` and `LPT`.
+/// * `CONIN$` and `CONOUT$` are reserved for the console input and output.
+///   These are separate devices, not `CON` with a suffix.
+///
+/// This only examines the string so it works the same on every platform.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::is_reserved_device_name;
+///
+/// assert!(is_reserved_device_name("con"));
+/// assert!(is_reserved_device_name("NUL.txt"));
+/// assert!(is_reserved_device_name("com1 .log"));
+/// assert!(is_reserved_device_name("LPT\u{b9}"));
+/// assert!(is_reserved_device_name("CONOUT$"));
+/// assert!(!is_reserved_device_name("console"));
+/// assert!(!is_reserved_device_name("COM0"));
+/// ```
+pub fn is_reserved_device_name(name: &str) -> bool {
+    let stem = match name.find(['.', ':']) {
+        Some(pos) => &name[..pos],
+        None => name,
+    };
+    let stem = stem.trim_end_matches(' ').as_bytes();
+    match_pattern! {
+        stem;
+        [C, O, N] | [P, R, N] | [A, U, X] | [N, U, L] => true,
+        [C, O, N, I, N, '$'] | [C, O, N, O, U, T, '$'] => true,
+        [C, O, M, _] | [L, P, T, _] => matches!(stem[3], b'1'..=b'9'),
+        // Superscript one, two and three are encoded as `0xC2` followed by
+        // `0xB9`, `0xB2` and `0xB3`.
+        [C, O, M, 0xC2, _] | [L, P, T, 0xC2, _] => matches!(stem[4], 0xB9 | 0xB2 | 0xB3),
+        _ => false
+    }
+}
+
+/// Test if the last component of a path is a reserved DOS device name.
+///
+/// Both `\\` and `/` are treated as separators and any drive prefix is
+/// ignored, so the drive relative path `C:nul` is checked as `nul`. See
+/// [`is_reserved_device_name`] for the rules used.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::has_reserved_device_name;
+/// use std::path::Path;
+///
+/// assert!(has_reserved_device_name(Path::new(r"C:\path\to\nul.txt")));
+/// assert!(has_reserved_device_name(Path::new("path/to/aux")));
+/// assert!(has_reserved_device_name(Path::new("C:nul")));
+/// assert!(!has_reserved_device_name(Path::new(r"C:\con\file.txt")));
+/// ```
+#[cfg(feature = "std")]
+pub fn has_reserved_device_name(path: &std::path::Path) -> bool {
+    // Device names are ASCII so a lossy conversion doesn't change the result.
+    let path = path.to_string_lossy();
+    let (_, path) = WinPathKind::split_str(&path);
+    let name = path.rsplit(['\\', '/']).next().unwrap_or_default();
+    is_reserved_device_name(name)
+}
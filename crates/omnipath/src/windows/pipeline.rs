@@ -0,0 +1,270 @@
+//! Run a sequence of path conversions in a coherent order.
+//!
+//! Checking the order of the steps works on all platforms but only Windows
+//! can run them.
+
+use std::fmt;
+#[cfg(any(doc, windows))]
+use std::io;
+#[cfg(any(doc, windows))]
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+#[cfg(any(doc, windows))]
+use super::WinPathExt;
+
+/// A conversion that can be added to a [`Pipeline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PipelineStep {
+    /// [`WinPathExt::win_clean`].
+    Clean,
+    /// [`WinPathExt::win_absolute`].
+    Absolute,
+    /// [`WinPathExt::to_winuser_path`].
+    Winuser,
+    /// [`WinPathExt::to_verbatim`].
+    Verbatim,
+    /// [`WinPathExt::to_verbatim_exact`].
+    VerbatimExact,
+    /// [`WinPathExt::to_verbatim_if_needed`].
+    VerbatimIfNeeded,
+}
+impl PipelineStep {
+    /// Test if the step always returns a verbatim path.
+    fn is_verbatim(self) -> bool {
+        matches!(self, Self::Verbatim | Self::VerbatimExact)
+    }
+
+    #[cfg(any(doc, windows))]
+    fn run(self, path: &Path) -> io::Result<PathBuf> {
+        match self {
+            Self::Clean => path.win_clean(),
+            Self::Absolute => path.win_absolute(),
+            Self::Winuser => path.to_winuser_path(),
+            Self::Verbatim => path.to_verbatim(),
+            Self::VerbatimExact => path.to_verbatim_exact(),
+            Self::VerbatimIfNeeded => path.to_verbatim_if_needed().map(|path| path.into_owned()),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Clean => "clean",
+            Self::Absolute => "absolute",
+            Self::Winuser => "winuser",
+            Self::Verbatim => "verbatim",
+            Self::VerbatimExact => "verbatim_exact",
+            Self::VerbatimIfNeeded => "verbatim_if_needed",
+        }
+    }
+}
+
+/// Two steps of a [`Pipeline`] that don't work well together.
+///
+/// Depending on the steps this is either an error, which stops the pipeline
+/// from running, or a warning that a step is redundant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PipelineConflict {
+    /// The step that was added first.
+    pub earlier: PipelineStep,
+    /// The step that was added later.
+    pub later: PipelineStep,
+    reason: &'static str,
+}
+impl PipelineConflict {
+    /// Why the steps conflict.
+    pub fn reason(&self) -> &'static str {
+        self.reason
+    }
+}
+impl fmt::Display for PipelineConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` after `{}`: {}", self.later.name(), self.earlier.name(), self.reason)
+    }
+}
+impl std::error::Error for PipelineConflict {}
+
+/// How a new step relates to an earlier one.
+enum Rule {
+    Ok,
+    Redundant(&'static str),
+    Reject(&'static str),
+}
+
+/// Each rule is based on what `earlier` guarantees about its output.
+/// `VerbatimIfNeeded` usually returns the path unchanged so it's only
+/// redundant before another verbatim step.
+fn rule(earlier: PipelineStep, later: PipelineStep) -> Rule {
+    use PipelineStep::*;
+    match (earlier, later) {
+        (earlier, Clean | Absolute) if earlier.is_verbatim() => {
+            Rule::Reject("verbatim paths are never cleaned or made absolute")
+        }
+        (earlier, Winuser) if earlier.is_verbatim() => {
+            Rule::Reject("this undoes the earlier conversion to a verbatim path")
+        }
+        (earlier, Verbatim | VerbatimExact | VerbatimIfNeeded) if earlier.is_verbatim() => {
+            Rule::Reject("the path has already been converted to a verbatim path")
+        }
+        (VerbatimIfNeeded, Verbatim | VerbatimExact) => {
+            Rule::Redundant("the later step makes every path verbatim")
+        }
+        (VerbatimIfNeeded, VerbatimIfNeeded) => Rule::Redundant("converting twice has no effect"),
+        (Clean | Absolute, Absolute) => Rule::Redundant("`absolute` also cleans the path"),
+        (Clean, Clean) => Rule::Redundant("cleaning twice has no effect"),
+        (Winuser, Winuser) => Rule::Redundant("converting twice has no effect"),
+        _ => Rule::Ok,
+    }
+}
+
+/// A sequence of path conversions.
+///
+/// Some conversions only make sense in a particular order. For example,
+/// verbatim paths are never cleaned so cleaning a path after making it
+/// verbatim silently does nothing. Each step is checked against the steps
+/// before it when it's added:
+///
+/// * Steps that would have no effect or would undo an earlier step are
+///   errors. These are cleaning, making absolute or converting to a user path
+///   after `verbatim` or `verbatim_exact`, and any verbatim step after them.
+/// * Steps that are redundant are warnings. For example, `absolute` also
+///   cleans the path so `clean` before it isn't needed.
+///
+/// `verbatim_if_needed` only changes some paths so any step can follow it.
+///
+/// Errors are returned by [`check`](Pipeline::check) and
+/// [`run`](Pipeline::run). Warnings are returned by
+/// [`warnings`](Pipeline::warnings) and don't stop the pipeline from running.
+/// Checking works on all platforms but running is Windows only.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::Pipeline;
+///
+/// // Verbatim paths are never cleaned.
+/// let pipeline = Pipeline::new().verbatim_exact().clean();
+/// assert!(pipeline.check().is_err());
+///
+/// // `absolute` already cleans the path.
+/// let pipeline = Pipeline::new().clean().absolute();
+/// assert!(pipeline.check().is_ok());
+/// assert_eq!(pipeline.warnings().len(), 1);
+///
+/// #[cfg(windows)]
+/// {
+///     use omnipath::windows::PipelineStep;
+///     use std::path::Path;
+///
+///     let pipeline = Pipeline::new().absolute().verbatim_if_needed();
+///     let output = pipeline.run(Path::new(r"C:\path\.\to\file.txt")).unwrap();
+///     assert_eq!(output.path, Path::new(r"C:\path\to\file.txt"));
+///     assert_eq!(output.trace[0].0, PipelineStep::Absolute);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    steps: Vec<PipelineStep>,
+    warnings: Vec<PipelineConflict>,
+    error: Option<PipelineConflict>,
+}
+impl Pipeline {
+    /// Create an empty pipeline. Running it returns the path unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a step.
+    pub fn step(mut self, step: PipelineStep) -> Self {
+        for &earlier in &self.steps {
+            let conflict = |reason| PipelineConflict { earlier, later: step, reason };
+            match rule(earlier, step) {
+                Rule::Ok => {}
+                Rule::Redundant(reason) => self.warnings.push(conflict(reason)),
+                Rule::Reject(reason) => {
+                    self.error.get_or_insert(conflict(reason));
+                }
+            }
+        }
+        self.steps.push(step);
+        self
+    }
+
+    /// Add a [`PipelineStep::Clean`] step.
+    pub fn clean(self) -> Self {
+        self.step(PipelineStep::Clean)
+    }
+
+    /// Add a [`PipelineStep::Absolute`] step.
+    pub fn absolute(self) -> Self {
+        self.step(PipelineStep::Absolute)
+    }
+
+    /// Add a [`PipelineStep::Winuser`] step.
+    pub fn winuser(self) -> Self {
+        self.step(PipelineStep::Winuser)
+    }
+
+    /// Add a [`PipelineStep::Verbatim`] step.
+    pub fn verbatim(self) -> Self {
+        self.step(PipelineStep::Verbatim)
+    }
+
+    /// Add a [`PipelineStep::VerbatimExact`] step.
+    pub fn verbatim_exact(self) -> Self {
+        self.step(PipelineStep::VerbatimExact)
+    }
+
+    /// Add a [`PipelineStep::VerbatimIfNeeded`] step.
+    pub fn verbatim_if_needed(self) -> Self {
+        self.step(PipelineStep::VerbatimIfNeeded)
+    }
+
+    /// The steps in the order they will run.
+    pub fn steps(&self) -> &[PipelineStep] {
+        &self.steps
+    }
+
+    /// Steps that are redundant.
+    pub fn warnings(&self) -> &[PipelineConflict] {
+        &self.warnings
+    }
+
+    /// Test if the steps are in a coherent order.
+    ///
+    /// Returns the first conflict that is an error.
+    pub fn check(&self) -> Result<(), PipelineConflict> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// [Windows only] Run each step in order.
+    ///
+    /// An error is returned if [`check`](Pipeline::check) fails or if any
+    /// step fails.
+    #[cfg(any(doc, windows))]
+    pub fn run(&self, path: &Path) -> io::Result<PipelineOutput> {
+        if let Some(error) = self.error {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, error));
+        }
+        let mut path = PathBuf::from(path);
+        let mut trace = Vec::with_capacity(self.steps.len());
+        for &step in &self.steps {
+            path = step.run(&path)?;
+            trace.push((step, path.clone()));
+        }
+        Ok(PipelineOutput { path, trace })
+    }
+}
+
+/// [Windows only] The result of [`Pipeline::run`].
+#[cfg(any(doc, windows))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineOutput {
+    /// The final path.
+    pub path: PathBuf,
+    /// Each step that was run and the path it returned.
+    pub trace: Vec<(PipelineStep, PathBuf)>,
+}
@@ -0,0 +1,50 @@
+//! Quoting paths for Windows command lines.
+
+use std::iter;
+use std::path::Path;
+use std::string::String;
+
+/// Quote a path so it can be used as a single argument on a Windows command line.
+///
+/// The path is wrapped in double quotes. Embedded quotes and any backslashes
+/// that would otherwise escape a quote (including trailing backslashes) are
+/// escaped following the rules used by `CommandLineToArgvW` and the Microsoft
+/// C runtime.
+///
+/// Note that `cmd.exe` will still expand `%variables%` within the quotes.
+/// Paths that are not valid Unicode are converted lossily.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::cmd_quote;
+/// use std::path::Path;
+///
+/// assert_eq!(cmd_quote(Path::new(r"C:\Program Files\app")), r#""C:\Program Files\app""#);
+/// // Trailing backslashes are doubled so they don't escape the closing quote.
+/// assert_eq!(cmd_quote(Path::new(r"C:\path\to\dir\")), r#""C:\path\to\dir\\""#);
+/// // Embedded quotes are escaped.
+/// assert_eq!(cmd_quote(Path::new(r#"say "hi"\file"#)), r#""say \"hi\"\file""#);
+/// ```
+pub fn cmd_quote(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let mut quoted = String::with_capacity(path.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for c in path.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        if c == '"' {
+            // Escape the preceding backslashes as well as the quote itself.
+            backslashes = backslashes * 2 + 1;
+        }
+        quoted.extend(iter::repeat('\\').take(backslashes));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.extend(iter::repeat('\\').take(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
@@ -1,31 +1,45 @@
 //! [Windows only] Use the Windows API to perform path operations.
 
-use std::ffi::OsString;
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Write};
 use std::io;
 use std::iter::Iterator;
 use std::mem::MaybeUninit;
 #[cfg(not(doc))]
 use std::os::windows::ffi::{OsStrExt, OsStringExt};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Components, Path, PathBuf, Prefix};
 use std::ptr;
+use std::slice;
 use std::string::String;
 use std::vec::Vec;
 
-use super::kind::{ParsedUtf8Path, Win32Absolute, Win32Relative, WinPathKind};
+use crate::{Divergence, ResolutionKind, ResolvedPath};
 
-const VERBATIM_PREFIX: &str = r"\\?\";
-const UNC_PREFIX: &str = r"\\?\UNC\";
+use super::clean::{clean_utf8_with, subpath_needs_normalization};
+use super::kind::{
+    ParsedUtf8Path, VerbatimParseError, Win32Absolute, Win32Relative, WinPathKind, WinVerbatimKind,
+    MAX_PATH,
+};
+use super::name::{is_reserved_char, is_reserved_device_name};
+use super::utf8::{WinRegions, WinUtf8Path};
+use super::wide::WideScanner;
+
+const VERBATIM_PREFIX: &str = WinPathKind::VERBATIM_PREFIX_STR;
+const UNC_PREFIX: &str = WinPathKind::VERBATIM_UNC_PREFIX_STR;
 const COLON: u16 = ':' as u16;
 const SEP: u16 = '\\' as u16;
 const QUERY: u16 = '?' as u16;
 const DOT: u16 = '.' as u16;
+const SLASH: u16 = '/' as u16;
 
 /// [Windows only] Extension functions that use the Windows API to resolve paths.
 pub trait WinPathExt: Sealed {
     /// Makes the path absolute without resolving symlinks.
     ///
     /// Unlike canonicalize the path does not need to exist. This will also not
-    /// return verbatim paths unless given one.
+    /// return verbatim paths unless given one. An empty path is an error, the
+    /// same as `GetFullPathNameW`.
     ///
     /// # Example
     ///
@@ -46,13 +60,54 @@ pub trait WinPathExt: Sealed {
     /// ```
     fn win_absolute(&self) -> io::Result<PathBuf>;
 
-    /// Clean the path without making it absolute or changing its path prefix.
+    /// Makes the path absolute using `base` instead of the current directory.
     ///
-    /// This does the same cleaning as occurs when you pass a path to the Windows API.
+    /// This follows the same rules as [`win_absolute`][WinPathExt::win_absolute]
+    /// but never uses the process's current directory:
+    ///
+    /// * Relative paths (e.g. `path\to\file`) are joined to `base`.
+    /// * Root relative paths (e.g. `\path\to\file`) are joined to the drive or
+    ///   UNC share of `base`.
+    /// * Drive relative paths (e.g. `C:path\to\file`) are joined to `base` if
+    ///   `base` is on the same drive. Otherwise an error is returned because the
+    ///   current directory of the other drive isn't known.
+    /// * Absolute paths are the same as calling `win_absolute`.
     ///
-    /// # Limitations
+    /// `base` must be an absolute path. A verbatim `base` is converted using
+    /// [`to_winuser_path`][WinPathExt::to_winuser_path] and an error is
+    /// returned if it can't be.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let base = Path::new(r"C:\config\dir");
+    ///     assert_eq!(
+    ///         Path::new(r"..\file.txt").win_absolute_from(base).unwrap(),
+    ///         Path::new(r"C:\config\file.txt")
+    ///     );
+    ///     assert_eq!(
+    ///         Path::new(r"\file.txt").win_absolute_from(base).unwrap(),
+    ///         Path::new(r"C:\file.txt")
+    ///     );
+    ///     assert_eq!(
+    ///         Path::new(r"c:file.txt").win_absolute_from(base).unwrap(),
+    ///         Path::new(r"C:\config\dir\file.txt")
+    ///     );
+    ///     assert!(Path::new(r"D:file.txt").win_absolute_from(base).is_err());
+    /// }
+    /// ```
+    fn win_absolute_from(&self, base: &Path) -> io::Result<PathBuf>;
+
+    /// Clean the path without making it absolute or changing its path prefix.
     ///
-    /// This function will currently discard all `..` components after cleaning.
+    /// This does the same cleaning as occurs when you pass a path to the Windows API.
+    /// `..` components can't go above the root of an absolute path. Leading
+    /// `..` components of a relative path are kept.
     ///
     /// # Example
     ///
@@ -67,6 +122,9 @@ pub trait WinPathExt: Sealed {
     ///         path.win_clean().unwrap(),
     ///         Path::new(r"path\to\file")
     ///     );
+    ///
+    ///     let path = Path::new(r"..\..\x\.\y");
+    ///     assert_eq!(path.win_clean().unwrap(), Path::new(r"..\..\x\y"));
     /// }
     /// ```
     #[doc(hidden)]
@@ -76,6 +134,21 @@ pub trait WinPathExt: Sealed {
     ///
     /// If the path is not verbatim the the path is returned as-is.
     ///
+    /// Incomplete verbatim paths are handled as follows:
+    ///
+    /// * `\\?\`, `\\?\UNC` and `\\?\UNC\` are returned as-is.
+    /// * A drive without a root, such as `\\?\C:`, refers to the volume
+    ///   itself rather than the root directory so it's converted to the device
+    ///   path `\\.\C:`.
+    /// * Anything else that isn't a drive or UNC path, such as `\\?\C`, is
+    ///   converted to a device path (e.g. `\\.\C`).
+    ///
+    /// Volume GUID paths (e.g. `\\?\Volume{GUID}\`) and `\\?\GLOBALROOT`
+    /// paths are returned as-is. See
+    /// [`win_verbatim_kind`][WinPathExt::win_verbatim_kind] to identify them
+    /// and [`win_volume_to_drive`][WinPathExt::win_volume_to_drive] to convert
+    /// a volume GUID path to a drive path.
+    ///
     /// # Example
     ///
     /// ```
@@ -95,6 +168,24 @@ pub trait WinPathExt: Sealed {
     ///         path.to_winuser_path().unwrap(),
     ///         Path::new(r"\\server\share\file.txt")
     ///     );
+    ///
+    ///     // Incomplete verbatim paths.
+    ///     assert_eq!(Path::new(r"\\?\").to_winuser_path().unwrap(), Path::new(r"\\?\"));
+    ///     assert_eq!(Path::new(r"\\?\UNC").to_winuser_path().unwrap(), Path::new(r"\\?\UNC"));
+    ///     assert_eq!(Path::new(r"\\?\UNC\").to_winuser_path().unwrap(), Path::new(r"\\?\UNC\"));
+    ///     assert_eq!(Path::new(r"\\?\C:").to_winuser_path().unwrap(), Path::new(r"\\.\C:"));
+    ///     assert_eq!(Path::new(r"\\?\C").to_winuser_path().unwrap(), Path::new(r"\\.\C"));
+    ///
+    ///     let path = Path::new(r"\\?\GLOBALROOT\Device\HarddiskVolume3\file.txt");
+    ///     assert_eq!(path.to_winuser_path().unwrap(), path);
+    ///
+    ///     // A `/` in a verbatim path is not a separator so the path is
+    ///     // returned unchanged.
+    ///     let path = Path::new(r"\\?\C:\path/to\file.txt");
+    ///     assert_eq!(
+    ///         path.to_winuser_path().unwrap().as_os_str(),
+    ///         path.as_os_str()
+    ///     );
     /// }
     /// ```
     fn to_winuser_path(&self) -> io::Result<PathBuf>;
@@ -153,7 +244,9 @@ pub trait WinPathExt: Sealed {
     /// Convert to an exact verbatim path
     ///
     /// Unlike [`to_verbatim`][WinPathExt::to_verbatim], this will preserve the
-    /// exact path name, changing only the root of the path.
+    /// exact path name, changing only the root of the path. Relative paths are
+    /// joined to the current directory. For drive relative paths (e.g.
+    /// `D:file.txt`) this is the current directory of that drive.
     ///
     /// Warning: This can be risky unless you really mean it. For example,
     /// a `/` will be taken as a file named `/`. Similarly `.`, `..` will all
@@ -185,11 +278,506 @@ pub trait WinPathExt: Sealed {
     /// }
     /// ```
     fn to_verbatim_exact(&self) -> io::Result<PathBuf>;
+
+    /// Test if the path must be made verbatim to be used exactly as written.
+    ///
+    /// This is the same as [`WinUtf8Path::needs_verbatim`] except that the
+    /// length is tested after the path is made absolute, using
+    /// [`win_absolute`][WinPathExt::win_absolute]. An error is returned if
+    /// that fails, including if the path is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     assert!(!Path::new(r"C:\path\to\file.txt").needs_verbatim().unwrap());
+    ///     assert!(Path::new(r"C:\path\to\file.txt.").needs_verbatim().unwrap());
+    ///
+    ///     let long = "a".repeat(200);
+    ///     assert!(!Path::new(&long).needs_verbatim().unwrap());
+    ///     assert!(Path::new(&format!(r"{long}\{long}")).needs_verbatim().unwrap());
+    /// }
+    /// ```
+    fn needs_verbatim(&self) -> io::Result<bool>;
+
+    /// Convert to a verbatim path only if
+    /// [`needs_verbatim`][WinPathExt::needs_verbatim] returns `true`.
+    ///
+    /// Otherwise the path is returned unchanged, as are paths that are already
+    /// verbatim. Unlike [`to_verbatim`][WinPathExt::to_verbatim], names are
+    /// kept exactly as written so trailing dots and spaces are not trimmed.
+    /// Relative paths are joined to the current directory and `.` and `..`
+    /// components are resolved before the path is made verbatim.
+    ///
+    /// An error is returned if the path needs to be verbatim but it, or the
+    /// current directory, is not valid Unicode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new(r"C:\path\to\file.txt");
+    ///     assert_eq!(path.to_verbatim_if_needed().unwrap(), path);
+    ///
+    ///     let path = Path::new(r"C:\path\.\to\..\file.txt.");
+    ///     assert_eq!(
+    ///         path.to_verbatim_if_needed().unwrap(),
+    ///         Path::new(r"\\?\C:\path\file.txt.")
+    ///     );
+    /// }
+    /// ```
+    fn to_verbatim_if_needed(&self) -> io::Result<Cow<'_, Path>>;
+
+    /// Display the path with control characters escaped.
+    ///
+    /// Characters such as newlines or the terminal escape character are
+    /// written as `\u{..}` escapes so that printing a path can't break line
+    /// based output or spoof terminal output. Unpaired surrogates are escaped
+    /// in the same way. All other characters, including separators, are
+    /// written as-is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new("C:\\path\\to\\file\n.txt");
+    ///     assert_eq!(
+    ///         path.win_display_safe().to_string(),
+    ///         r"C:\path\to\file\u{a}.txt"
+    ///     );
+    ///
+    ///     let path = Path::new(r"C:\path\to\café.txt");
+    ///     assert_eq!(
+    ///         path.win_display_safe().escape_non_ascii().to_string(),
+    ///         r"C:\path\to\caf\u{e9}.txt"
+    ///     );
+    /// }
+    /// ```
+    fn win_display_safe(&self) -> DisplaySafe<'_>;
+
+    /// Resolve the path to both a verbatim path and a user path.
+    ///
+    /// The verbatim path ([`ResolvedPath::for_os`]) is for passing to the
+    /// Windows API and the user path ([`ResolvedPath::for_display`]) is for
+    /// showing to the user (e.g. in error messages). Both refer to the same
+    /// file. If the user path cannot be displayed without loss then
+    /// [`ResolvedPath::was_lossy`] is set.
+    ///
+    /// This only makes the path absolute once so it is cheaper than calling
+    /// both [`win_absolute`][WinPathExt::win_absolute] and
+    /// [`to_verbatim`][WinPathExt::to_verbatim]. Unlike `to_verbatim`, the
+    /// verbatim path is created from the absolute path so DOS device names
+    /// such as `NUL` are resolved in the same way as the Windows API would.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let resolved = Path::new(r"C:\path\to\..\file.txt").win_resolve_pair().unwrap();
+    ///     assert_eq!(resolved.for_os, Path::new(r"\\?\C:\path\file.txt"));
+    ///     assert_eq!(resolved.for_display, Path::new(r"C:\path\file.txt"));
+    ///     assert!(!resolved.was_lossy);
+    ///
+    ///     let resolved = Path::new(r"\\?\UNC\server\share\file.txt").win_resolve_pair().unwrap();
+    ///     assert_eq!(resolved.for_os, Path::new(r"\\?\UNC\server\share\file.txt"));
+    ///     assert_eq!(resolved.for_display, Path::new(r"\\server\share\file.txt"));
+    /// }
+    /// ```
+    fn win_resolve_pair(&self) -> io::Result<ResolvedPath>;
+
+    /// Display the path as a user path.
+    ///
+    /// The conversion is done by [`to_winuser_path`][WinPathExt::to_winuser_path]
+    /// when the path is formatted. If that fails then the original path is
+    /// displayed instead. Like [`Path::display`], unpaired surrogates are
+    /// replaced with `U+FFFD`.
+    ///
+    /// Nothing is allocated unless the path is a verbatim path.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new(r"\\?\C:\path\to\file.txt");
+    ///     assert_eq!(path.display_user().to_string(), r"C:\path\to\file.txt");
+    ///
+    ///     let path = Path::new(r"C:\path\to\file.txt");
+    ///     assert_eq!(format!("{}", path.display_user()), r"C:\path\to\file.txt");
+    /// }
+    /// ```
+    fn display_user(&self) -> DisplayUser<'_>;
+
+    /// Get the path that the OS will use.
+    ///
+    /// This is the same as [`win_absolute`][WinPathExt::win_absolute]. It is
+    /// intended for comparing with the path as it was written (see
+    /// [`win_literal_components`][WinPathExt::win_literal_components] and
+    /// [`win_divergence`][WinPathExt::win_divergence]).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new(r"C:\logs\..\Windows\System32\file");
+    ///     assert_eq!(path.win_effective().unwrap(), Path::new(r"C:\Windows\System32\file"));
+    /// }
+    /// ```
+    fn win_effective(&self) -> io::Result<PathBuf>;
+
+    /// Get the components of the path without resolving `..` components.
+    ///
+    /// Unlike [`win_effective`][WinPathExt::win_effective], `..` components
+    /// are kept. This is the same as [`Path::components`] so it is not an
+    /// exact copy of what's written: repeated separators are collapsed and `.`
+    /// components are dropped, except at the start of a relative path.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new(r"C:\logs\..\Windows");
+    ///     let components: Vec<_> = path.win_literal_components().map(|c| c.as_os_str()).collect();
+    ///     assert_eq!(components, ["C:", r"\", "logs", "..", "Windows"]);
+    ///
+    ///     let path = Path::new(r"C:\logs\\.\..\Windows");
+    ///     let components: Vec<_> = path.win_literal_components().map(|c| c.as_os_str()).collect();
+    ///     assert_eq!(components, ["C:", r"\", "logs", "..", "Windows"]);
+    /// }
+    /// ```
+    fn win_literal_components(&self) -> Components<'_>;
+
+    /// Find the first `..` component that leaves a directory named by the path.
+    ///
+    /// For example `C:\logs\..\Windows` names the `C:\logs` directory but
+    /// actually refers to `C:\Windows`. Returns `None` if the path never leaves
+    /// a directory that it names. Verbatim paths always return `None` because
+    /// `..` components are not resolved in verbatim paths. This does not access
+    /// the filesystem.
+    ///
+    /// The index is the position of the `..` component in
+    /// [`win_literal_components`][WinPathExt::win_literal_components].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let divergence = Path::new(r"C:\logs\..\Windows").win_divergence().unwrap();
+    ///     assert_eq!(divergence.index, 3);
+    ///     assert_eq!(divergence.left, Path::new(r"C:\logs"));
+    ///
+    ///     assert!(Path::new(r"C:\logs\file").win_divergence().is_none());
+    /// }
+    /// ```
+    fn win_divergence(&self) -> Option<Divergence>;
+
+    /// Get a path relative to `base`.
+    ///
+    /// Both paths are made absolute using [`win_absolute`][WinPathExt::win_absolute]
+    /// and verbatim paths are first converted using
+    /// [`to_winuser_path`][WinPathExt::to_winuser_path]. The drive letter or
+    /// UNC server and share must be the same for both paths, otherwise an
+    /// error is returned. Components are compared ignoring ASCII case. `..`
+    /// components are added for every component of `base` not shared with
+    /// the path.
+    ///
+    /// If both paths are the same then an empty path is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new(r"\\?\C:\foo");
+    ///     assert_eq!(path.win_relative_to(Path::new(r"C:\")).unwrap(), Path::new("foo"));
+    ///
+    ///     let path = Path::new(r"C:\Users\me\file.txt");
+    ///     assert_eq!(
+    ///         path.win_relative_to(Path::new(r"c:\users\other")).unwrap(),
+    ///         Path::new(r"..\me\file.txt")
+    ///     );
+    ///
+    ///     // Paths on different drives can't be relative to each other.
+    ///     assert!(path.win_relative_to(Path::new(r"D:\")).is_err());
+    ///     assert!(path.win_relative_to(Path::new(r"\\server\share")).is_err());
+    /// }
+    /// ```
+    fn win_relative_to(&self, base: &Path) -> io::Result<PathBuf>;
+
+    /// Get the [`WinPathKind`] of the path.
+    ///
+    /// This is the same as [`WinPathKind::from_os_str`]. The path doesn't need
+    /// to be valid Unicode and nothing is allocated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::{WinPathExt, WinPathKind};
+    ///     use std::path::Path;
+    ///
+    ///     assert_eq!(Path::new(r"\\?\C:\x").win_path_kind(), WinPathKind::Verbatim);
+    ///     assert_eq!(Path::new("C:file").win_path_kind(), WinPathKind::DriveRelative(b'C' as u16));
+    ///     assert_eq!(Path::new("é:\\file").win_path_kind(), WinPathKind::Drive('é' as u16));
+    /// }
+    /// ```
+    fn win_path_kind(&self) -> WinPathKind;
+
+    /// Get the root of an absolute path.
+    ///
+    /// The root is the prefix of the path followed by a `\`, if there is one.
+    /// For example the drive root `C:\`, the UNC root `\\server\share\` or
+    /// the device root `\\.\pipe\`. Verbatim paths have the verbatim
+    /// equivalent (e.g. `\\?\UNC\server\share\`).
+    ///
+    /// Returns `None` for relative, drive relative and root relative paths.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     assert_eq!(Path::new(r"C:\path\to\file").win_root().unwrap(), Path::new(r"C:\"));
+    ///     assert_eq!(
+    ///         Path::new(r"\\server\share\file").win_root().unwrap(),
+    ///         Path::new(r"\\server\share\")
+    ///     );
+    ///     assert_eq!(
+    ///         Path::new(r"\\?\UNC\server\share\file").win_root().unwrap(),
+    ///         Path::new(r"\\?\UNC\server\share\")
+    ///     );
+    ///     assert_eq!(Path::new(r"\\.\pipe\name").win_root().unwrap(), Path::new(r"\\.\pipe\"));
+    ///
+    ///     assert_eq!(Path::new(r"path\to\file").win_root(), None);
+    ///     assert_eq!(Path::new(r"C:file").win_root(), None);
+    ///     assert_eq!(Path::new(r"\file").win_root(), None);
+    /// }
+    /// ```
+    fn win_root(&self) -> Option<PathBuf>;
+
+    /// Get the length of the root of an absolute path.
+    ///
+    /// This is the length in bytes of the [`win_root`][WinPathExt::win_root]
+    /// of the path as it's written in the `OsStr`. Returns `None` for relative,
+    /// drive relative and root relative paths.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     assert_eq!(Path::new(r"C:\path\to\file").win_root_len(), Some(3));
+    ///     assert_eq!(Path::new(r"\\server\share\file").win_root_len(), Some(15));
+    ///     assert_eq!(Path::new(r"C:file").win_root_len(), None);
+    /// }
+    /// ```
+    fn win_root_len(&self) -> Option<usize>;
+
+    /// Join a relative path to this directory and prepare it for creating a
+    /// file.
+    ///
+    /// This does the following, in order:
+    ///
+    /// 1. Checks each component of `rel` and collects any [`PathProblem`]s.
+    /// 2. Joins `rel` to this path and cleans the result using
+    ///    [`clean_utf8`](super::clean_utf8). Unlike
+    ///    [`win_absolute`][WinPathExt::win_absolute], this doesn't turn
+    ///    names like `NUL` into device paths.
+    /// 3. Converts the result to a verbatim path if it's too long for
+    ///    `MAX_PATH` or contains a reserved device name.
+    ///
+    /// This path must be absolute. A verbatim path is converted using
+    /// [`to_winuser_path`][WinPathExt::to_winuser_path] and an error is
+    /// returned if it can't be. `rel` must be a relative path (e.g. not
+    /// `\file` or `C:\file`) and its `..` components must not go above this
+    /// path. A drive relative `rel` such as `a:b` is joined like any other
+    /// file name and reported as a [`PathProblem::ReservedChar`]. An error is
+    /// also returned if either path is not valid Unicode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::{PathProblem, WinPathExt};
+    ///     use std::path::Path;
+    ///
+    ///     let root = Path::new(r"C:\output");
+    ///     let prepared = root.win_prepare_output_path(Path::new(r"a\.\b\file.txt")).unwrap();
+    ///     assert_eq!(prepared.os_path, Path::new(r"C:\output\a\b\file.txt"));
+    ///     assert!(!prepared.made_verbatim);
+    ///     assert!(prepared.warnings.is_empty());
+    ///
+    ///     let prepared = root.win_prepare_output_path(Path::new(r"nul.txt")).unwrap();
+    ///     assert_eq!(prepared.os_path, Path::new(r"\\?\C:\output\nul.txt"));
+    ///     assert_eq!(prepared.display_path, Path::new(r"C:\output\nul.txt"));
+    ///     assert!(prepared.made_verbatim);
+    ///     assert_eq!(prepared.warnings, [PathProblem::ReservedName("nul.txt".into())]);
+    ///
+    ///     let prepared = root.win_prepare_output_path(Path::new(r"file.")).unwrap();
+    ///     assert_eq!(prepared.os_path, Path::new(r"C:\output\file"));
+    ///     assert_eq!(prepared.warnings, [PathProblem::TrailingDotOrSpace("file.".into())]);
+    ///
+    ///     assert!(root.win_prepare_output_path(Path::new(r"..\file.txt")).is_err());
+    /// }
+    /// ```
+    fn win_prepare_output_path(&self, rel: &Path) -> io::Result<PreparedPath>;
+
+    /// Split the path into its parent, file name and extension as Windows
+    /// will see them.
+    ///
+    /// Windows removes trailing dots and spaces from the file name, so unlike
+    /// [`Path::extension`] the extension of `photo.JPG ` is `JPG` and
+    /// `archive...` has no extension. The extension is everything after the
+    /// last `.` in the trimmed file name, unless the only `.` is at the start
+    /// (e.g. `.hidden`). The file name is `None` if the path doesn't have one
+    /// or if it's entirely dots and spaces. Verbatim paths are not trimmed.
+    ///
+    /// The parent is the same as [`Path::parent`] and is not changed. For a
+    /// device path such as `\\.\pipe\name` this is the device root
+    /// (`\\.\pipe\`), which is not a directory in the usual sense.
+    ///
+    /// The parts borrow from the path unless it isn't valid Unicode. An error
+    /// is returned if the path contains a null.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let parts = Path::new(r"C:\photos\photo.JPG ").win_split_file().unwrap();
+    ///     assert_eq!(parts.parent, Some(Path::new(r"C:\photos")));
+    ///     assert_eq!(parts.file_name.as_deref(), Some("photo.JPG".as_ref()));
+    ///     assert_eq!(parts.extension.as_deref(), Some("JPG".as_ref()));
+    ///
+    ///     let parts = Path::new(r"C:\archive...").win_split_file().unwrap();
+    ///     assert_eq!(parts.file_name.as_deref(), Some("archive".as_ref()));
+    ///     assert_eq!(parts.extension, None);
+    ///
+    ///     // Verbatim paths are used exactly as given.
+    ///     let parts = Path::new(r"\\?\C:\archive. ").win_split_file().unwrap();
+    ///     assert_eq!(parts.file_name.as_deref(), Some("archive. ".as_ref()));
+    ///     assert_eq!(parts.extension.as_deref(), Some(" ".as_ref()));
+    /// }
+    /// ```
+    fn win_split_file(&self) -> io::Result<WinFileParts<'_>>;
+
+    /// Get the kind of a verbatim path.
+    ///
+    /// Returns `None` if the path isn't verbatim. Device names that aren't
+    /// valid Unicode are [`WinVerbatimKind::Other`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::{WinPathExt, WinVerbatimKind};
+    ///     use std::path::Path;
+    ///
+    ///     let path = Path::new(r"\\?\Volume{26a21bda-a627-11d7-9931-806e6f6e6963}\file.txt");
+    ///     assert_eq!(path.win_verbatim_kind(), Some(WinVerbatimKind::VolumeGuid));
+    ///     assert_eq!(Path::new(r"C:\file.txt").win_verbatim_kind(), None);
+    /// }
+    /// ```
+    fn win_verbatim_kind(&self) -> Option<WinVerbatimKind>;
+
+    /// Convert a volume GUID path to a path that starts with a drive letter.
+    ///
+    /// The volume is looked up using `GetVolumePathNamesForVolumeNameW`. If
+    /// it's mounted on a drive then the `\\?\Volume{GUID}` prefix is replaced
+    /// with the drive (e.g. `C:`). Otherwise `None` is returned, including if
+    /// the volume is only mounted on a directory.
+    ///
+    /// An error is returned if the path is not a volume GUID path or if the
+    /// volume can't be found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     assert!(Path::new(r"C:\file.txt").win_volume_to_drive().is_err());
+    /// }
+    /// ```
+    fn win_volume_to_drive(&self) -> io::Result<Option<PathBuf>>;
+
+    /// Split the path into regions for highlighting.
+    ///
+    /// This is the same as [`WinUtf8Path::regions`]. The ranges are byte
+    /// offsets into the path as it's stored in the `OsStr`, the same as
+    /// [`win_root_len`][WinPathExt::win_root_len]. Paths that aren't valid
+    /// Unicode are supported.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[cfg(windows)]
+    /// {
+    ///     use omnipath::windows::WinPathExt;
+    ///     use std::path::Path;
+    ///
+    ///     let regions = Path::new(r"C:\dir\file.txt").win_regions();
+    ///     assert_eq!(regions.prefix, Some(0..2));
+    ///     assert_eq!(regions.root, Some(2..3));
+    ///     assert_eq!(regions.parent, Some(3..7));
+    ///     assert_eq!(regions.stem, Some(7..11));
+    ///     assert_eq!(regions.extension, Some(11..15));
+    /// }
+    /// ```
+    fn win_regions(&self) -> WinRegions;
 }
 impl WinPathExt for Path {
     fn win_absolute(&self) -> io::Result<PathBuf> {
         if self.as_os_str().is_empty() {
-            return Ok(PathBuf::new());
+            return Err(empty_error());
         }
         if let Some(std::path::Component::Prefix(prefix)) = self.components().next() {
             if prefix.kind().is_verbatim() {
@@ -199,6 +787,49 @@ impl WinPathExt for Path {
         let path = to_wide(self)?;
         absolute_inner(&path, |path| OsString::from_wide(path).into())
     }
+    fn win_absolute_from(&self, base: &Path) -> io::Result<PathBuf> {
+        if self.as_os_str().is_empty() {
+            return Err(empty_error());
+        }
+        let (kind, rest) = WideScanner::new(self.as_os_str()).split();
+        if kind.is_absolute() {
+            return self.win_absolute();
+        }
+        let base = base.to_winuser_path()?;
+        let root = match base.components().next() {
+            Some(Component::Prefix(prefix)) if base.has_root() && !prefix.kind().is_verbatim() => {
+                prefix
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "expected an absolute, non-verbatim path as the base",
+                ))
+            }
+        };
+
+        let mut joined = match kind {
+            WinPathKind::RootRelative => OsString::from(root.as_os_str()),
+            WinPathKind::DriveRelative(drive) => {
+                let same_drive = match root.kind() {
+                    Prefix::Disk(base_drive) => u8::try_from(drive)
+                        .map_or(false, |drive| drive.eq_ignore_ascii_case(&base_drive)),
+                    _ => false,
+                };
+                if !same_drive {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "the drive relative path is not on the same drive as the base",
+                    ));
+                }
+                base.into_os_string()
+            }
+            _ => base.into_os_string(),
+        };
+        joined.push(r"\");
+        joined.push(OsString::from_wide(&rest.collect::<Vec<u16>>()));
+        Path::new(&joined).win_absolute()
+    }
     #[doc(hidden)]
     fn win_clean(&self) -> io::Result<PathBuf> {
         let path = match self.to_str() {
@@ -213,6 +844,13 @@ impl WinPathExt for Path {
             return Ok(path.into());
         }
         let (prefix, path) = parsed.parts();
+        let is_relative = matches!(
+            parsed.kind(),
+            WinPathKind::CurrentDirectoryRelative | WinPathKind::DriveRelative(_)
+        );
+        if is_relative {
+            return clean_relative(prefix, path);
+        }
 
         // 2. use `absolute` on the path, using `\\.\` for the prefix
         let path = String::from_iter([r"\\.\", path]);
@@ -225,35 +863,46 @@ impl WinPathExt for Path {
         })
     }
     fn to_winuser_path(&self) -> io::Result<PathBuf> {
-        let path = match self.to_str() {
-            Some(path) => path,
-            None => return Ok(self.into()),
-        };
-        let (prefix, subpath) = match Win32Absolute::from_verbatim_str(path) {
+        let path: Vec<u16> = self.as_os_str().encode_wide().collect();
+        let (prefix, subpath) = match Win32Absolute::from_verbatim_wide(&path) {
             Ok(result) => result,
-            Err(_) => return Ok(path.into()),
+            // Already a user path.
+            Err(VerbatimParseError::NotVerbatim) => return Ok(self.into()),
+            // There's nothing to convert.
+            Err(VerbatimParseError::EmptyPath) => return Ok(self.into()),
         };
-        let prefix = match prefix {
-            Win32Absolute::Drive(_) => return Ok(subpath.into()),
-            Win32Absolute::Device => r"\\.\",
-            Win32Absolute::Unc => {
-                if subpath.is_empty() {
-                    r"\\"
-                } else {
-                    r"\"
-                }
+        // A `/` in a verbatim path is part of a file name. Converting it to a
+        // win32 path would turn it into a separator.
+        if path.contains(&SLASH) {
+            return Ok(self.into());
+        }
+        let prefix: &[u16] = match prefix {
+            Win32Absolute::Drive(_) => &[],
+            // There's no user path for a verbatim prefix with nothing after it.
+            Win32Absolute::Device if subpath.is_empty() => return Ok(self.into()),
+            // Volume GUID and NT object namespace paths are always verbatim.
+            Win32Absolute::Device
+                if matches!(
+                    WinVerbatimKind::from_verbatim_wide(&path),
+                    Some(WinVerbatimKind::VolumeGuid | WinVerbatimKind::GlobalRoot)
+                ) =>
+            {
+                return Ok(self.into())
             }
+            Win32Absolute::Unc if subpath.is_empty() || subpath == [SEP] => return Ok(self.into()),
+            Win32Absolute::Device => &[SEP, SEP, b'.' as u16, SEP],
+            Win32Absolute::Unc => &[SEP],
         };
-        let mut win32 = String::with_capacity(prefix.len() + subpath.len());
-        win32.push_str(prefix);
-        win32.push_str(subpath);
+        let mut win32 = Vec::with_capacity(prefix.len() + subpath.len());
+        win32.extend_from_slice(prefix);
+        win32.extend_from_slice(subpath);
+        let win32 = PathBuf::from(OsString::from_wide(&win32));
 
         // Test if path is unchanged by a call to absolute.
-        let win32 = Path::new(&win32);
         if win32 == win32.win_absolute().unwrap_or_default() {
-            Ok(win32.into())
+            Ok(win32)
         } else {
-            Ok(path.into())
+            Ok(self.into())
         }
     }
 
@@ -269,6 +918,12 @@ impl WinPathExt for Path {
                 return Ok(self.into());
             }
         }
+        // Absolute paths that are already clean don't need the OS.
+        let lexical =
+            self.to_str().and_then(|path| WinUtf8Path::from_utf8(path).to_verbatim_lexical());
+        if let Some(verbatim) = lexical {
+            return Ok(verbatim.into_string().into());
+        }
 
         let mut path = to_wide(self)?;
         let ends_with_sep = path.ends_with(&[b'\\' as u16, 0]) || path.ends_with(&[b'/' as u16, 0]);
@@ -277,25 +932,8 @@ impl WinPathExt for Path {
             path.push(b'\\' as u16);
             path.push(0);
         }
-        absolute_inner(&path, |mut absolute| {
-            let prefix = match absolute {
-                // C:\ => \\?\C:\
-                [_, COLON, SEP, ..] => VERBATIM_PREFIX,
-                // \\.\ => \\?\
-                [SEP, SEP, DOT, SEP, ..] => {
-                    absolute = &absolute[4..];
-                    VERBATIM_PREFIX
-                }
-                // Leave \\?\ and \??\ as-is.
-                [SEP, SEP, QUERY, SEP, ..] | [SEP, QUERY, QUERY, SEP, ..] => "",
-                // \\ => \\?\UNC\
-                [SEP, SEP, ..] => {
-                    absolute = &absolute[2..];
-                    UNC_PREFIX
-                }
-                // Anything else we leave alone.
-                _ => "",
-            };
+        absolute_inner(&path, |absolute| {
+            let (prefix, mut absolute) = verbatim_parts(absolute);
             if !ends_with_sep && absolute.ends_with(&[b'\\' as u16]) {
                 absolute = &absolute[..absolute.len() - 1];
             }
@@ -328,6 +966,22 @@ impl WinPathExt for Path {
                 absolute.push(path);
                 Ok(absolute.into())
             }
+            // A drive relative path (e.g. `D:file.txt`) is relative to the
+            // current directory of that drive, not the process' current directory.
+            [drive, COLON, rest @ ..]
+                if !matches!((*drive, rest.first()), (SEP, _) | (_, Some(&SLASH))) =>
+            {
+                let mut absolute = resolve_prefix(Win32Relative::DriveRelative(*drive))?;
+                if !rest.is_empty() {
+                    let mut joined = absolute.into_os_string();
+                    if joined.encode_wide().last() != Some(SEP) {
+                        joined.push(r"\");
+                    }
+                    joined.push(OsString::from_wide(rest));
+                    absolute = joined.into();
+                }
+                absolute.to_verbatim_exact()
+            }
             [SEP, SEP, DOT, SEP, ..] => {
                 path[2] = b'?' as u16;
                 Ok(OsString::from_wide(&path).into())
@@ -359,6 +1013,372 @@ impl WinPathExt for Path {
             .to_verbatim_exact(),
         }
     }
+
+    fn needs_verbatim(&self) -> io::Result<bool> {
+        // Replacing invalid UTF-16 doesn't change the length of the path or
+        // whether a component needs to be verbatim.
+        let path = self.to_string_lossy();
+        let path = WinUtf8Path::from_utf8(&path);
+        if path.needs_verbatim() {
+            return Ok(true);
+        }
+        if path.kind() == WinPathKind::Verbatim {
+            return Ok(false);
+        }
+        Ok(self.win_absolute()?.as_os_str().encode_wide().count() > MAX_PATH)
+    }
+
+    fn to_verbatim_if_needed(&self) -> io::Result<Cow<'_, Path>> {
+        if self.win_path_kind() == WinPathKind::Verbatim || !self.needs_verbatim()? {
+            return Ok(Cow::Borrowed(self));
+        }
+        let unicode_error =
+            || io::Error::new(io::ErrorKind::InvalidInput, "expected a Unicode path");
+        let path = self.to_str().ok_or_else(unicode_error)?;
+        let (kind, rest) = WinPathKind::split_str(path);
+        let absolute = match Win32Relative::from_kind(kind) {
+            Some(relative) => {
+                let base = resolve_prefix(relative)?;
+                let mut absolute =
+                    base.into_os_string().into_string().map_err(|_| unicode_error())?;
+                if !absolute.ends_with('\\') {
+                    absolute.push('\\');
+                }
+                absolute.push_str(rest);
+                Cow::Owned(absolute)
+            }
+            None => Cow::Borrowed(path),
+        };
+        let cleaned = clean_utf8_with(&absolute, false);
+        Ok(Cow::Owned(Path::new(&*cleaned).to_verbatim_exact()?))
+    }
+
+    fn win_display_safe(&self) -> DisplaySafe<'_> {
+        DisplaySafe { path: self, escape_non_ascii: false }
+    }
+
+    fn win_resolve_pair(&self) -> io::Result<ResolvedPath> {
+        if let Some(std::path::Component::Prefix(prefix)) = self.components().next() {
+            if prefix.kind().is_verbatim() {
+                let for_display = self.to_winuser_path()?;
+                return Ok(ResolvedPath::new(self.into(), for_display));
+            }
+        }
+        let path = to_wide(self)?;
+        let mut for_display = PathBuf::new();
+        let for_os = absolute_inner(&path, |absolute| {
+            for_display = OsString::from_wide(absolute).into();
+            let (prefix, absolute) = verbatim_parts(absolute);
+            let mut verbatim = OsString::from(prefix);
+            verbatim.push(OsString::from_wide(absolute));
+            verbatim.into()
+        })?;
+        Ok(ResolvedPath::new(for_os, for_display))
+    }
+
+    fn display_user(&self) -> DisplayUser<'_> {
+        DisplayUser { path: self }
+    }
+
+    fn win_effective(&self) -> io::Result<PathBuf> {
+        self.win_absolute()
+    }
+
+    fn win_literal_components(&self) -> Components<'_> {
+        self.components()
+    }
+
+    fn win_relative_to(&self, base: &Path) -> io::Result<PathBuf> {
+        let path = self.to_winuser_path()?.win_absolute()?;
+        let base = base.to_winuser_path()?.win_absolute()?;
+
+        let same_kind = core::mem::discriminant(&WinPathKind::from_str(&path.to_string_lossy()))
+            == core::mem::discriminant(&WinPathKind::from_str(&base.to_string_lossy()));
+        let mut path_components = path.components().peekable();
+        let mut base_components = base.components().peekable();
+        match (path_components.next(), base_components.next()) {
+            (Some(Component::Prefix(a)), Some(Component::Prefix(b)))
+                if same_kind && a.as_os_str().eq_ignore_ascii_case(b.as_os_str()) => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the path and base have different roots",
+                ))
+            }
+        }
+        while let (Some(a), Some(b)) = (path_components.peek(), base_components.peek()) {
+            if !a.as_os_str().eq_ignore_ascii_case(b.as_os_str()) {
+                break;
+            }
+            path_components.next();
+            base_components.next();
+        }
+
+        let mut relative = PathBuf::new();
+        for _ in base_components {
+            relative.push("..");
+        }
+        relative.extend(path_components);
+        Ok(relative)
+    }
+
+    fn win_divergence(&self) -> Option<Divergence> {
+        if let Some(std::path::Component::Prefix(prefix)) = self.components().next() {
+            if prefix.kind().is_verbatim() {
+                return None;
+            }
+        }
+        crate::find_divergence(self)
+    }
+
+    fn win_path_kind(&self) -> WinPathKind {
+        WideScanner::new(self.as_os_str()).kind()
+    }
+
+    fn win_root(&self) -> Option<PathBuf> {
+        let (prefix, has_root) = split_root(self)?;
+        let mut root = OsString::from(prefix.as_os_str());
+        if has_root {
+            root.push(r"\");
+        }
+        Some(root.into())
+    }
+
+    fn win_root_len(&self) -> Option<usize> {
+        let (prefix, has_root) = split_root(self)?;
+        Some(prefix.as_os_str().len() + usize::from(has_root))
+    }
+
+    fn win_prepare_output_path(&self, rel: &Path) -> io::Result<PreparedPath> {
+        let base = self.to_winuser_path()?;
+        match base.win_path_kind() {
+            WinPathKind::Drive(_) | WinPathKind::Unc | WinPathKind::Device => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "expected an absolute, non-verbatim path as the base",
+                ))
+            }
+        }
+        // A drive relative path such as `a:b` is a file name with a reserved
+        // character once it's joined to the base.
+        match rel.win_path_kind() {
+            WinPathKind::CurrentDirectoryRelative | WinPathKind::DriveRelative(_) => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "expected a relative path to join",
+                ))
+            }
+        }
+        let (base, rel) = match (base.to_str(), rel.to_str()) {
+            (Some(base), Some(rel)) => (base, rel),
+            _ => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "expected a Unicode path"))
+            }
+        };
+
+        let mut warnings = Vec::new();
+        let mut has_reserved_name = false;
+        let mut depth = 0_usize;
+        for component in rel.split(['\\', '/']) {
+            match component {
+                "" | "." => continue,
+                ".." => {
+                    depth = depth.checked_sub(1).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            "the relative path must not go above the base",
+                        )
+                    })?;
+                    continue;
+                }
+                _ => depth += 1,
+            }
+            if component.chars().any(is_reserved_char) {
+                warnings.push(PathProblem::ReservedChar(component.into()));
+            }
+            if component.ends_with(['.', ' ']) {
+                warnings.push(PathProblem::TrailingDotOrSpace(component.into()));
+            }
+            if is_reserved_device_name(component) {
+                has_reserved_name = true;
+                warnings.push(PathProblem::ReservedName(component.into()));
+            }
+            if component.encode_utf16().count() > 255 {
+                warnings.push(PathProblem::ComponentTooLong(component.into()));
+            }
+        }
+
+        let mut joined = String::with_capacity(base.len() + 1 + rel.len());
+        joined.push_str(base);
+        if !joined.ends_with(['\\', '/']) {
+            joined.push('\\');
+        }
+        joined.push_str(rel);
+        let display_path = PathBuf::from(super::clean_utf8(&joined).into_owned());
+
+        let is_long = display_path.as_os_str().encode_wide().count() > MAX_PATH;
+        if is_long {
+            warnings.push(PathProblem::LongPath);
+        }
+        let made_verbatim = is_long || has_reserved_name;
+        let os_path =
+            if made_verbatim { display_path.to_verbatim_exact()? } else { display_path.clone() };
+        Ok(PreparedPath { os_path, display_path, made_verbatim, warnings })
+    }
+
+    fn win_split_file(&self) -> io::Result<WinFileParts<'_>> {
+        if self.as_os_str().encode_wide().any(|w| w == 0) {
+            return Err(io::Error::new(io::ErrorKind::Other, "paths must not contain nulls"));
+        }
+        let trim = self.win_path_kind() != WinPathKind::Verbatim;
+        let (file_name, extension) = match self.file_name() {
+            Some(name) => split_file_name(name, trim),
+            None => (None, None),
+        };
+        Ok(WinFileParts { parent: self.parent(), file_name, extension })
+    }
+
+    fn win_regions(&self) -> WinRegions {
+        // An unpaired surrogate and its replacement character are both three
+        // bytes long so the offsets are the same.
+        let path = self.to_string_lossy();
+        WinUtf8Path::from_utf8(&path).regions()
+    }
+
+    fn win_verbatim_kind(&self) -> Option<WinVerbatimKind> {
+        let path: Vec<u16> = self.as_os_str().encode_wide().collect();
+        WinVerbatimKind::from_verbatim_wide(&path)
+    }
+
+    fn win_volume_to_drive(&self) -> io::Result<Option<PathBuf>> {
+        // `\\?\Volume{26a21bda-a627-11d7-9931-806e6f6e6963}`
+        const VOLUME_LEN: usize = 48;
+        let path: Vec<u16> = self.as_os_str().encode_wide().collect();
+        if WinVerbatimKind::from_verbatim_wide(&path) != Some(WinVerbatimKind::VolumeGuid) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "expected a volume GUID path"));
+        }
+        let (volume, rest) = path.split_at(VOLUME_LEN);
+        // The volume name must end with a separator.
+        let mut name = Vec::with_capacity(VOLUME_LEN + 2);
+        name.extend_from_slice(volume);
+        name.extend_from_slice(&[SEP, 0]);
+        let mount_points = volume_path_names(&name)?;
+        let drive = mount_points.split(|&w| w == 0).find(|point| matches!(point, [_, COLON, SEP]));
+        Ok(drive.map(|drive| {
+            let mut path = OsString::from_wide(&drive[..2]);
+            path.push(OsString::from_wide(rest));
+            // `\\?\Volume{GUID}` is the root of the volume.
+            if rest.is_empty() {
+                path.push(r"\");
+            }
+            path.into()
+        }))
+    }
+}
+
+/// Get the prefix of an absolute path and whether it's followed by a root
+/// separator.
+///
+/// `components` reports a root for UNC and device paths even if there's no
+/// separator so this only looks at what's actually written.
+fn split_root(path: &Path) -> Option<(std::path::PrefixComponent<'_>, bool)> {
+    let prefix = match path.components().next() {
+        Some(Component::Prefix(prefix)) => prefix,
+        _ => return None,
+    };
+    let prefix_len = prefix.as_os_str().encode_wide().count();
+    let has_root = match path.as_os_str().encode_wide().nth(prefix_len) {
+        Some(SEP) => true,
+        // `/` is not a separator after a verbatim prefix.
+        Some(SLASH) => !prefix.kind().is_verbatim(),
+        _ => false,
+    };
+    match prefix.kind() {
+        // Without a root these are drive relative.
+        Prefix::Disk(_) if !has_root => None,
+        _ => Some((prefix, has_root)),
+    }
+}
+
+/// Clean a relative path, keeping any `..` components that go above the start
+/// of the path.
+fn clean_relative(prefix: &str, path: &str) -> io::Result<PathBuf> {
+    // A dummy device that the rest of the path is cleaned relative to.
+    const ROOT: &str = r"\\.\X";
+
+    // Remove the `..` components that the OS would drop because they'd go
+    // above the root. Empty and `.` components don't count towards the depth.
+    // Only the last component has all its trailing dots and spaces trimmed so
+    // it's also removed if that leaves it empty.
+    let mut parents = 0;
+    let mut depth = 0_usize;
+    let mut rest = String::with_capacity(ROOT.len() + 1 + path.len());
+    rest.push_str(ROOT);
+    let mut ends_with_sep = path.ends_with(['\\', '/']);
+    let mut components = path.split(['\\', '/']).peekable();
+    while let Some(component) = components.next() {
+        let is_last = components.peek().is_none();
+        match component {
+            "" | "." => {}
+            ".." => {
+                if depth == 0 {
+                    parents += 1;
+                    continue;
+                }
+                depth -= 1;
+            }
+            _ if is_last && component.trim_end_matches(['.', ' ']).is_empty() => {
+                ends_with_sep = true;
+            }
+            _ => depth += 1,
+        }
+        rest.push('\\');
+        rest.push_str(component);
+    }
+
+    let rest = to_wide(Path::new(&rest))?;
+    absolute_inner(&rest, |cleaned| {
+        let cleaned = cleaned.get(ROOT.len()..).unwrap_or_default();
+        let cleaned = cleaned.strip_prefix(&[SEP]).unwrap_or(cleaned);
+        let mut os_path = OsString::from(prefix);
+        for index in 0..parents {
+            if index != 0 {
+                os_path.push(r"\");
+            }
+            os_path.push("..");
+        }
+        if !cleaned.is_empty() {
+            if parents != 0 {
+                os_path.push(r"\");
+            }
+            os_path.push(OsString::from_wide(cleaned));
+        } else if parents != 0 && ends_with_sep {
+            os_path.push(r"\");
+        }
+        os_path.into()
+    })
+}
+
+fn empty_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "an empty path cannot be made absolute")
+}
+
+/// Split an absolute path into the verbatim prefix to use and the rest of the path.
+fn verbatim_parts(absolute: &[u16]) -> (&'static str, &[u16]) {
+    match absolute {
+        // C:\ => \\?\C:\
+        [_, COLON, SEP, ..] => (VERBATIM_PREFIX, absolute),
+        // \\.\ => \\?\
+        [SEP, SEP, DOT, SEP, ..] => (VERBATIM_PREFIX, &absolute[4..]),
+        // Leave \\?\ and \??\ as-is.
+        [SEP, SEP, QUERY, SEP, ..] | [SEP, QUERY, QUERY, SEP, ..] => ("", absolute),
+        // \\ => \\?\UNC\
+        [SEP, SEP, ..] => (UNC_PREFIX, &absolute[2..]),
+        // Anything else we leave alone.
+        _ => ("", absolute),
+    }
 }
 
 /// [Windows only] Turns a relative Windows prefix into an absolute path.
@@ -374,6 +1394,188 @@ pub fn resolve_prefix(prefix: Win32Relative) -> io::Result<PathBuf> {
     }
 }
 
+/// Split a file name into the (optionally trimmed) name and its extension.
+fn split_file_name(name: &OsStr, trim: bool) -> (Option<Cow<'_, OsStr>>, Option<Cow<'_, OsStr>>) {
+    const DOT: u16 = b'.' as u16;
+    const SPACE: u16 = b' ' as u16;
+    if let Some(name) = name.to_str() {
+        let name = if trim { name.trim_end_matches(['.', ' ']) } else { name };
+        if name.is_empty() {
+            return (None, None);
+        }
+        let extension = match name.rfind('.') {
+            None | Some(0) => None,
+            Some(dot) => Some(Cow::Borrowed(OsStr::new(&name[dot + 1..]))),
+        };
+        return (Some(Cow::Borrowed(OsStr::new(name))), extension);
+    }
+    let mut name: Vec<u16> = name.encode_wide().collect();
+    if trim {
+        let len = name.iter().rposition(|&w| w != DOT && w != SPACE).map_or(0, |i| i + 1);
+        name.truncate(len);
+    }
+    if name.is_empty() {
+        return (None, None);
+    }
+    let extension = match name.iter().rposition(|&w| w == DOT) {
+        None | Some(0) => None,
+        Some(dot) => Some(Cow::Owned(OsString::from_wide(&name[dot + 1..]))),
+    };
+    (Some(Cow::Owned(OsString::from_wide(&name))), extension)
+}
+
+/// [Windows only] The parts of a file path as Windows will see them.
+///
+/// This is returned by [`WinPathExt::win_split_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinFileParts<'a> {
+    /// The path without its final component, the same as [`Path::parent`].
+    pub parent: Option<&'a Path>,
+    /// The final component with trailing dots and spaces removed.
+    pub file_name: Option<Cow<'a, OsStr>>,
+    /// The extension of the trimmed file name, without the leading `.`.
+    pub extension: Option<Cow<'a, OsStr>>,
+}
+
+/// [Windows only] A path prepared for creating a file.
+///
+/// This is returned by [`WinPathExt::win_prepare_output_path`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedPath {
+    /// The path to pass to OS APIs.
+    pub os_path: PathBuf,
+    /// The path to show to the user.
+    pub display_path: PathBuf,
+    /// `true` if `os_path` is a verbatim path.
+    pub made_verbatim: bool,
+    /// Problems found with the components of the path.
+    pub warnings: Vec<PathProblem>,
+}
+
+/// [Windows only] A problem with a component of a path.
+///
+/// See [`WinPathExt::win_prepare_output_path`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathProblem {
+    /// The component contains a character that can't be used in a file name
+    /// (e.g. `?` or `:`), so creating it will usually fail.
+    ReservedChar(String),
+    /// The component ends with a dot or space, which will be trimmed.
+    TrailingDotOrSpace(String),
+    /// The component is a reserved DOS device name (e.g. `NUL`). The path was
+    /// made verbatim so it can be created but other programs may not be able
+    /// to open it.
+    ReservedName(String),
+    /// The component is longer than 255 UTF-16 code units, which most
+    /// filesystems don't allow.
+    ComponentTooLong(String),
+    /// The path is longer than `MAX_PATH` so it was made verbatim. Other
+    /// programs may not be able to open it.
+    LongPath,
+}
+impl fmt::Display for PathProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReservedChar(name) => write!(f, "{:?} contains a reserved character", name),
+            Self::TrailingDotOrSpace(name) => {
+                write!(f, "{:?} ends with a dot or space that will be trimmed", name)
+            }
+            Self::ReservedName(name) => write!(f, "{:?} is a reserved device name", name),
+            Self::ComponentTooLong(name) => write!(f, "{:?} is too long for a file name", name),
+            Self::LongPath => f.write_str("the path is longer than MAX_PATH"),
+        }
+    }
+}
+
+/// [Windows only] Display a path with control characters escaped.
+///
+/// This is created by [`WinPathExt::win_display_safe`].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplaySafe<'a> {
+    path: &'a Path,
+    escape_non_ascii: bool,
+}
+impl<'a> DisplaySafe<'a> {
+    /// Also escape all non-ASCII characters.
+    pub fn escape_non_ascii(mut self) -> Self {
+        self.escape_non_ascii = true;
+        self
+    }
+}
+impl fmt::Display for DisplaySafe<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in char::decode_utf16(self.path.as_os_str().encode_wide()) {
+            match c {
+                Ok(c) if c.is_control() || (self.escape_non_ascii && !c.is_ascii()) => {
+                    write!(f, "\\u{{{:x}}}", c as u32)?
+                }
+                Ok(c) => f.write_char(c)?,
+                Err(e) => write!(f, "\\u{{{:x}}}", e.unpaired_surrogate())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// [Windows only] Display a path as a user path.
+///
+/// This is created by [`WinPathExt::display_user`].
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayUser<'a> {
+    path: &'a Path,
+}
+impl fmt::Display for DisplayUser<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(std::path::Component::Prefix(prefix)) = self.path.components().next() {
+            if prefix.kind().is_verbatim() {
+                if let Ok(user) = self.path.to_winuser_path() {
+                    return user.display().fmt(f);
+                }
+            }
+        }
+        self.path.display().fmt(f)
+    }
+}
+
+/// `win_absolute` tagged with how the path was resolved.
+pub(crate) fn absolute_tagged(path: &Path) -> io::Result<(PathBuf, ResolutionKind)> {
+    fn drive_char(drive: u16) -> char {
+        char::from_u32(drive.into()).unwrap_or(char::REPLACEMENT_CHARACTER)
+    }
+    let kind = path.win_path_kind();
+    let absolute = path.win_absolute()?;
+    let tag = match kind {
+        WinPathKind::CurrentDirectoryRelative => ResolutionKind::JoinedCwd,
+        WinPathKind::DriveRelative(drive) => ResolutionKind::JoinedDriveCwd(drive_char(drive)),
+        WinPathKind::RootRelative => {
+            let drive = match absolute.components().next() {
+                Some(Component::Prefix(prefix)) => match prefix.kind() {
+                    Prefix::Disk(drive) => Some(char::from(drive)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            ResolutionKind::JoinedRoot(drive)
+        }
+        WinPathKind::Drive(_) | WinPathKind::Unc | WinPathKind::Device | WinPathKind::Verbatim => {
+            ResolutionKind::WasAbsolute
+        }
+    };
+    Ok((absolute, tag))
+}
+
+/// Test if `win_absolute` may change the path.
+///
+/// This errs on the side of returning `true`.
+pub(crate) fn needs_normalization(path: &Path) -> bool {
+    let path = match path.to_str() {
+        Some(path) => path,
+        None => return true,
+    };
+    let (kind, subpath) = WinPathKind::split_str(path);
+    path.contains('/') || subpath_needs_normalization(kind, subpath)
+}
+
 /// Make a non-verbatim path absolute.
 fn absolute_inner<F>(path: &[u16], f: F) -> io::Result<PathBuf>
 where
@@ -381,22 +1583,79 @@ where
 {
     debug_assert!(!path.starts_with(&[b'\\' as u16, b'\\' as u16, b'?' as u16, b'\\' as u16]));
     assert_eq!(path.last(), Some(&0));
-    unsafe {
-        const MAX_PATH: u16 = i16::MAX as u16;
-        let mut buffer: [MaybeUninit<u16>; MAX_PATH as usize] = MaybeUninit::uninit().assume_init();
-        let capacity = MAX_PATH as u32;
-        let len = c::GetFullPathNameW(
-            path.as_ptr(),
-            capacity,
-            buffer.as_mut_ptr().cast(),
-            ptr::null_mut(),
-        );
-        if len == 0 {
-            Err(io::Error::last_os_error())
-        } else {
-            let path = &*((&buffer[..len as usize]) as *const _ as *const [u16]);
-            Ok(f(path))
+
+    // Most paths fit in a small stack buffer. Otherwise allocate a buffer of
+    // the required size and try again.
+    const STACK_LEN: usize = 512;
+    let mut stack_buffer = [MaybeUninit::<u16>::uninit(); STACK_LEN];
+    let mut required = match full_path_name(path, &mut stack_buffer)? {
+        Ok(absolute) => return Ok(f(absolute)),
+        Err(required) => required,
+    };
+    // The current directory can be changed between calls so the required
+    // size may grow.
+    loop {
+        let mut heap_buffer = Vec::with_capacity(required);
+        match full_path_name(path, heap_buffer.spare_capacity_mut())? {
+            Ok(absolute) => return Ok(f(absolute)),
+            Err(len) => required = len,
+        }
+    }
+}
+
+/// Call `GetFullPathNameW` with the given buffer.
+///
+/// Returns the absolute path written to the buffer or, if the buffer is too
+/// small, the required buffer size.
+fn full_path_name<'a>(
+    path: &[u16],
+    buffer: &'a mut [MaybeUninit<u16>],
+) -> io::Result<Result<&'a [u16], usize>> {
+    let capacity = u32::try_from(buffer.len()).unwrap_or(u32::MAX);
+    let len = unsafe {
+        c::GetFullPathNameW(path.as_ptr(), capacity, buffer.as_mut_ptr().cast(), ptr::null_mut())
+    };
+    if len == 0 {
+        Err(io::Error::last_os_error())
+    } else if len < capacity {
+        // SAFETY: On success, `len` code units (not including the null) have been initialized.
+        Ok(Ok(unsafe { slice::from_raw_parts(buffer.as_ptr().cast(), len as usize) }))
+    } else {
+        // On failure, `len` is the required size including the null.
+        Ok(Err(len as usize))
+    }
+}
+
+/// Call `GetVolumePathNamesForVolumeNameW`.
+///
+/// Returns the null separated list of drives and directories the volume is
+/// mounted on.
+fn volume_path_names(volume: &[u16]) -> io::Result<Vec<u16>> {
+    const ERROR_MORE_DATA: i32 = 234;
+    debug_assert_eq!(volume.last(), Some(&0));
+
+    let mut len: u32 = 64;
+    loop {
+        let mut buffer: Vec<u16> = Vec::new();
+        buffer.resize(len as usize, 0);
+        let mut required = 0;
+        let result = unsafe {
+            c::GetVolumePathNamesForVolumeNameW(
+                volume.as_ptr(),
+                buffer.as_mut_ptr(),
+                len,
+                &mut required,
+            )
+        };
+        if result != 0 {
+            buffer.truncate(required as usize);
+            return Ok(buffer);
+        }
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(ERROR_MORE_DATA) {
+            return Err(error);
         }
+        len = required.max(len.saturating_mul(2));
     }
 }
 
@@ -421,6 +1680,7 @@ fn to_wide(path: &Path) -> io::Result<Vec<u16>> {
 
 #[allow(nonstandard_style, clippy::style)]
 mod c {
+    type BOOL = i32;
     type DWORD = u32;
     type LPCWSTR = *const u16;
     type LPWSTR = *mut u16;
@@ -432,6 +1692,12 @@ mod c {
             lpBuffer: LPWSTR,
             lpFilePart: *mut LPWSTR,
         ) -> DWORD;
+        pub fn GetVolumePathNamesForVolumeNameW(
+            lpszVolumeName: LPCWSTR,
+            lpszVolumePathNames: LPWSTR,
+            cchBufferLength: DWORD,
+            lpcchReturnLength: *mut DWORD,
+        ) -> BOOL;
     }
 }
 
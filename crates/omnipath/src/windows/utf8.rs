@@ -0,0 +1,633 @@
+//! Windows paths stored as UTF-8 strings.
+
+use alloc::borrow::{Cow, ToOwned};
+use alloc::string::String;
+use core::borrow::Borrow;
+use core::fmt;
+use core::ops::{Deref, Range};
+
+use super::clean::{clean_utf8, subpath_needs_normalization};
+use super::kind::{
+    ParsedUtf8Path, VerbatimParseError, VerbatimStr, Win32Absolute, WinPathKind, MAX_PATH,
+};
+use super::name::is_reserved_device_name;
+
+/// A borrowed Windows path stored as UTF-8.
+///
+/// This manipulates paths as text, using the Windows rules for prefixes and
+/// separators, so it works the same on every platform. Both `\` and `/` are
+/// separators, except in verbatim paths where only `\` is.
+///
+/// Comparisons are exact. They don't ignore case or normalize the path.
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::{WinPathKind, WinUtf8Path};
+///
+/// let path = WinUtf8Path::from_utf8(r"C:\path\to\..\file.txt");
+/// assert_eq!(path.kind(), WinPathKind::Drive(b'C' as u16));
+/// assert_eq!(path.file_name(), Some("file.txt"));
+/// assert_eq!(path.parent().unwrap(), r"C:\path\to\..");
+/// assert_eq!(path.clean().as_utf8(), r"C:\path\file.txt");
+/// assert_eq!(path.parent().unwrap().join("other.txt"), r"C:\path\to\..\other.txt");
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct WinUtf8Path {
+    inner: str,
+}
+impl WinUtf8Path {
+    /// Wrap a string as a Windows path.
+    pub fn from_utf8(path: &str) -> &Self {
+        // SAFETY: `WinUtf8Path` is a `repr(transparent)` wrapper around `str`.
+        unsafe { &*(path as *const str as *const Self) }
+    }
+
+    /// Get the path as a string.
+    pub fn as_utf8(&self) -> &str {
+        &self.inner
+    }
+
+    /// Get the kind of path.
+    pub fn kind(&self) -> WinPathKind {
+        WinPathKind::from_str(&self.inner)
+    }
+
+    /// Split the path into its prefix and the rest of the path.
+    ///
+    /// For drive paths the prefix includes the root separator (e.g. `C:\`).
+    /// For UNC paths it includes the server and share (e.g. `\\server\share`).
+    /// Otherwise it's the same as [`WinPathKind::split_str`].
+    pub fn parts(&self) -> (&str, &str) {
+        ParsedUtf8Path::from_utf8(&self.inner).parts()
+    }
+
+    /// Get the server name of a UNC path.
+    ///
+    /// Both `\\server\share` and verbatim `\\?\UNC\server\share` paths are
+    /// supported. Returns `None` for other kinds of path or if the server
+    /// name is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use omnipath::windows::WinUtf8Path;
+    ///
+    /// let path = WinUtf8Path::from_utf8(r"//server/share/file.txt");
+    /// assert_eq!(path.unc_server(), Some("server"));
+    /// assert_eq!(path.unc_share(), Some("share"));
+    ///
+    /// let path = WinUtf8Path::from_utf8(r"\\?\UNC\::1\share");
+    /// assert_eq!(path.unc_server(), Some("::1"));
+    /// assert_eq!(WinUtf8Path::from_utf8(r"\\server").unc_share(), None);
+    /// ```
+    pub fn unc_server(&self) -> Option<&str> {
+        ParsedUtf8Path::from_utf8(&self.inner).unc_server()
+    }
+
+    /// Get the share name of a UNC path.
+    ///
+    /// Returns `None` if there is no server name or if the share name is
+    /// missing or empty (e.g. `\\server` or `\\server\\file`). See
+    /// [`unc_server`](Self::unc_server).
+    pub fn unc_share(&self) -> Option<&str> {
+        ParsedUtf8Path::from_utf8(&self.inner).unc_share()
+    }
+
+    /// Clean the path using [`clean_utf8`].
+    pub fn clean(&self) -> Cow<'_, WinUtf8Path> {
+        match clean_utf8(&self.inner) {
+            Cow::Borrowed(path) => Cow::Borrowed(Self::from_utf8(path)),
+            Cow::Owned(path) => Cow::Owned(WinUtf8PathBuf::from(path)),
+        }
+    }
+
+    /// Get the path without its last component.
+    ///
+    /// Returns `None` if the path is empty or is only a prefix or root (e.g.
+    /// `C:\`, `\\server\share` or `\\.\pipe`). The parent of a relative path
+    /// with a single component is the empty path.
+    pub fn parent(&self) -> Option<&WinUtf8Path> {
+        let root_len = self.root_len();
+        let is_sep = self.separator_fn();
+        let rest = self.inner.get(root_len..)?.trim_end_matches(is_sep);
+        if rest.is_empty() {
+            return None;
+        }
+        let name_start = rest.rfind(is_sep).map_or(0, |i| i + 1);
+        let parent = &rest[..name_start];
+        let parent = match parent.trim_end_matches(is_sep) {
+            // Keep the root separator.
+            "" => &parent[..parent.len().min(1)],
+            parent => parent,
+        };
+        self.inner.get(..root_len + parent.len()).map(Self::from_utf8)
+    }
+
+    /// Get the last component of the path.
+    ///
+    /// Trailing separators are ignored. Returns `None` if there are no
+    /// components after the prefix or if the last component is `.` or `..`.
+    pub fn file_name(&self) -> Option<&str> {
+        let is_sep = self.separator_fn();
+        let rest = self.inner.get(self.root_len()..)?.trim_end_matches(is_sep);
+        match rest.rsplit(is_sep).next() {
+            None | Some("" | "." | "..") => None,
+            name => name,
+        }
+    }
+
+    /// Join a path onto this one.
+    ///
+    /// * A relative `path` is appended, adding a `\` separator if needed.
+    /// * A root relative `path` (e.g. `\file.txt`) replaces everything after
+    ///   the prefix of this path.
+    /// * Any other `path` replaces this path.
+    pub fn join(&self, path: &str) -> WinUtf8PathBuf {
+        let mut joined = self.to_owned();
+        joined.push(path);
+        joined
+    }
+
+    /// Append an extension to the file name.
+    ///
+    /// Unlike replacing the extension, any existing extension is kept (e.g.
+    /// `file.tar` becomes `file.tar.gz`). A leading `.` in `ext` is ignored.
+    /// The rest of the path is not changed.
+    ///
+    /// Returns `None` if the path has no file name or ends with a separator.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use omnipath::windows::WinUtf8Path;
+    ///
+    /// let path = WinUtf8Path::from_utf8(r"C:\path\.\file.tar");
+    /// assert_eq!(path.add_extension("gz").unwrap(), r"C:\path\.\file.tar.gz");
+    /// assert_eq!(WinUtf8Path::from_utf8("file").add_extension(".txt").unwrap(), "file.txt");
+    /// assert_eq!(WinUtf8Path::from_utf8(r"C:\path\").add_extension("txt"), None);
+    /// ```
+    pub fn add_extension(&self, ext: &str) -> Option<WinUtf8PathBuf> {
+        if self.inner.ends_with(self.separator_fn()) {
+            return None;
+        }
+        self.file_name()?;
+        let ext = ext.strip_prefix('.').unwrap_or(ext);
+        let mut path = String::with_capacity(self.inner.len() + 1 + ext.len());
+        path.push_str(&self.inner);
+        if !ext.is_empty() {
+            path.push('.');
+            path.push_str(ext);
+        }
+        Some(WinUtf8PathBuf::from(path))
+    }
+
+    /// Convert an absolute path to a verbatim path without using the OS.
+    ///
+    /// This only succeeds if the result is the same as
+    /// [`WinPathExt::to_verbatim`](crate::windows::WinPathExt::to_verbatim)
+    /// would return. That's the case for verbatim paths, which are returned
+    /// unchanged, and for drive (with an uppercase drive letter) and UNC
+    /// paths that only use `\` separators and don't contain any empty, `.` or
+    /// `..` components, components with trailing dots or spaces or reserved
+    /// device names. `None` is returned for any other path.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use omnipath::windows::WinUtf8Path;
+    ///
+    /// let path = WinUtf8Path::from_utf8(r"C:\data\file.txt");
+    /// assert_eq!(path.to_verbatim_lexical().unwrap(), r"\\?\C:\data\file.txt");
+    ///
+    /// let path = WinUtf8Path::from_utf8(r"\\server\share\file.txt");
+    /// assert_eq!(path.to_verbatim_lexical().unwrap(), r"\\?\UNC\server\share\file.txt");
+    ///
+    /// // These need the OS to make them absolute or clean them.
+    /// assert_eq!(WinUtf8Path::from_utf8(r"data\file.txt").to_verbatim_lexical(), None);
+    /// assert_eq!(WinUtf8Path::from_utf8(r"C:\data\..\file.txt").to_verbatim_lexical(), None);
+    /// assert_eq!(WinUtf8Path::from_utf8(r"C:\data\nul").to_verbatim_lexical(), None);
+    /// ```
+    pub fn to_verbatim_lexical(&self) -> Option<WinUtf8PathBuf> {
+        let (kind, subpath) = WinPathKind::split_str(&self.inner);
+        if kind == WinPathKind::Verbatim {
+            return Some(self.to_owned());
+        }
+        if subpath_needs_normalization(kind, subpath) {
+            return None;
+        }
+        let mut verbatim =
+            String::with_capacity(self.inner.len() + WinPathKind::VERBATIM_UNC_PREFIX_STR.len());
+        match kind {
+            WinPathKind::Drive(_) => {
+                verbatim.push_str(WinPathKind::VERBATIM_PREFIX_STR);
+                verbatim.push_str(&self.inner);
+            }
+            WinPathKind::Unc => {
+                verbatim.push_str(WinPathKind::VERBATIM_UNC_PREFIX_STR);
+                verbatim.push_str(subpath);
+            }
+            _ => return None,
+        }
+        Some(WinUtf8PathBuf::from(verbatim))
+    }
+
+    /// Test if the path must be verbatim to be used exactly as written.
+    ///
+    /// Windows changes a non-verbatim path when it makes it absolute. This
+    /// returns `true` if that would change the meaning of the path or if the
+    /// path is too long. That is, if:
+    ///
+    /// * the path is longer than `MAX_PATH` (259 UTF-16 code units),
+    /// * a component is longer than 255 UTF-16 code units,
+    /// * a component ends with a dot or space, which would be trimmed, or
+    /// * a component is a reserved device name such as `NUL`.
+    ///
+    /// `.` and `..` components of a non-verbatim path are resolved by Windows
+    /// so they don't need a verbatim path. For a path that's already verbatim
+    /// this tests if it needs to stay verbatim. Its `.` and `..` components,
+    /// empty components and `/` are part of the file names so they also need
+    /// a verbatim path, as does `\\?\` on its own.
+    ///
+    /// The length is of the path as written. Making a relative path absolute
+    /// makes it longer, which
+    /// [`WinPathExt::needs_verbatim`](crate::windows::WinPathExt::needs_verbatim)
+    /// takes into account.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use omnipath::windows::WinUtf8Path;
+    ///
+    /// assert!(!WinUtf8Path::from_utf8(r"C:\path\..\file.txt").needs_verbatim());
+    /// assert!(WinUtf8Path::from_utf8(r"C:\path\file.txt.").needs_verbatim());
+    /// assert!(WinUtf8Path::from_utf8(r"C:\path\nul").needs_verbatim());
+    ///
+    /// // `..` is a file name in a verbatim path.
+    /// assert!(!WinUtf8Path::from_utf8(r"\\?\C:\path\file.txt").needs_verbatim());
+    /// assert!(WinUtf8Path::from_utf8(r"\\?\C:\path\..\file.txt").needs_verbatim());
+    /// ```
+    pub fn needs_verbatim(&self) -> bool {
+        const MAX_COMPONENT: usize = 255;
+
+        let path = &self.inner;
+        // The path without any verbatim prefix, its length once `\\?\` is
+        // replaced and whether it has a device name.
+        let (subpath, len, is_device, is_verbatim) = match VerbatimStr::new(path) {
+            Ok(verbatim) => {
+                let (kind, subpath) = verbatim.win32_parts();
+                if subpath.contains('/') {
+                    return true;
+                }
+                let prefix_len = match kind {
+                    Win32Absolute::Drive(_) => 0,
+                    Win32Absolute::Unc => r"\".len(),
+                    Win32Absolute::Device => WinPathKind::DEVICE_PREFIX_STR.len(),
+                };
+                let len = prefix_len + subpath.encode_utf16().count();
+                let subpath = subpath.strip_prefix('\\').unwrap_or(subpath);
+                (subpath, len, kind == Win32Absolute::Device, true)
+            }
+            Err(VerbatimParseError::EmptyPath) => return true,
+            Err(VerbatimParseError::NotVerbatim) => {
+                let (kind, subpath) = WinPathKind::split_str(path);
+                (subpath, path.encode_utf16().count(), kind == WinPathKind::Device, false)
+            }
+        };
+        if len > MAX_PATH {
+            return true;
+        }
+
+        let mut components = subpath.split(['\\', '/']).peekable();
+        // The device name is part of the prefix.
+        if is_device {
+            components.next();
+        }
+        while let Some(component) = components.next() {
+            let is_last = components.peek().is_none();
+            let needs_verbatim = match component {
+                "" => is_verbatim && !is_last,
+                "." | ".." => is_verbatim,
+                _ => {
+                    component.encode_utf16().count() > MAX_COMPONENT
+                        || component.ends_with(['.', ' '])
+                        || is_reserved_device_name(component)
+                }
+            };
+            if needs_verbatim {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Split the path into regions for highlighting.
+    ///
+    /// See [`WinRegions`] for the regions. Together they cover the whole path,
+    /// in order and without overlapping.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use omnipath::windows::WinUtf8Path;
+    ///
+    /// let path = r"\\server\share\dir\file.tar.gz";
+    /// let regions = WinUtf8Path::from_utf8(path).regions();
+    /// assert_eq!(regions.prefix.map(|r| &path[r]), Some(r"\\"));
+    /// assert_eq!(regions.root.map(|r| &path[r]), Some(r"server\share\"));
+    /// assert_eq!(regions.parent.map(|r| &path[r]), Some(r"dir\"));
+    /// assert_eq!(regions.stem.map(|r| &path[r]), Some("file.tar"));
+    /// assert_eq!(regions.extension.map(|r| &path[r]), Some(".gz"));
+    /// ```
+    pub fn regions(&self) -> WinRegions {
+        let path = &self.inner;
+        let (kind, kind_len) = WinPathKind::from_str_with_len(path);
+        let root_len = self.root_len();
+        let prefix_end = match kind {
+            WinPathKind::Drive(_) | WinPathKind::DriveRelative(_) => root_len,
+            WinPathKind::Verbatim => match path.get(kind_len..kind_len + r"UNC\".len()) {
+                Some(unc) if unc.eq_ignore_ascii_case(r"UNC\") => kind_len + unc.len(),
+                _ => kind_len,
+            },
+            WinPathKind::Unc | WinPathKind::Device => kind_len,
+            WinPathKind::RootRelative | WinPathKind::CurrentDirectoryRelative => 0,
+        };
+        let is_sep = self.separator_fn();
+        let rest = path.get(root_len..).unwrap_or_default();
+        let root_end = match rest.chars().next() {
+            Some(c) if is_sep(c) => root_len + c.len_utf8(),
+            _ => root_len,
+        };
+
+        let rest = path.get(root_end..).unwrap_or_default();
+        let name_start = root_end + rest.rfind(is_sep).map_or(0, |i| i + 1);
+        let name = path.get(name_start..).unwrap_or_default();
+        let (parent_end, dot) = match name {
+            // A trailing separator, `.` or `..` is part of the parent.
+            "" | "." | ".." => (path.len(), None),
+            // A leading `.` doesn't start an extension.
+            _ => (name_start, name.rfind('.').filter(|&dot| dot > 0)),
+        };
+        let stem_end = dot.map_or(path.len(), |dot| name_start + dot);
+
+        let region = |start: usize, end: usize| if start < end { Some(start..end) } else { None };
+        WinRegions {
+            prefix: region(0, prefix_end),
+            root: region(prefix_end, root_end),
+            parent: region(root_end, parent_end),
+            stem: region(parent_end, stem_end),
+            extension: region(stem_end, path.len()),
+        }
+    }
+
+    /// The length of the prefix, not including any root separator.
+    ///
+    /// The device name is included for device paths. For verbatim paths the
+    /// drive, UNC server and share or device name is included.
+    fn root_len(&self) -> usize {
+        let path = &self.inner;
+        let (kind, len) = WinPathKind::from_str_with_len(path);
+        let rest = path.get(len..).unwrap_or_default();
+        match kind {
+            WinPathKind::Drive(_) => len - 1,
+            WinPathKind::DriveRelative(_) => len,
+            WinPathKind::Unc => ParsedUtf8Path::from_utf8(path).parts().0.len(),
+            WinPathKind::Device => len + component_end(rest, 1, |c| matches!(c, '\\' | '/')),
+            WinPathKind::Verbatim => match rest.get(..r"UNC\".len()) {
+                Some(unc) if unc.eq_ignore_ascii_case(r"UNC\") => {
+                    let unc = &rest[unc.len()..];
+                    len + r"UNC\".len() + component_end(unc, 2, |c| c == '\\')
+                }
+                _ => len + component_end(rest, 1, |c| c == '\\'),
+            },
+            WinPathKind::RootRelative | WinPathKind::CurrentDirectoryRelative => 0,
+        }
+    }
+
+    fn separator_fn(&self) -> fn(char) -> bool {
+        if self.kind() == WinPathKind::Verbatim {
+            |c| c == '\\'
+        } else {
+            |c| matches!(c, '\\' | '/')
+        }
+    }
+}
+
+/// The regions of a path, as byte ranges.
+///
+/// This is returned by [`WinUtf8Path::regions`] and
+/// [`WinPathExt::win_regions`](crate::windows::WinPathExt::win_regions).
+/// Regions that are empty are `None`. The regions are in the same order as
+/// the fields and together they cover the whole path. Trailing dots and spaces
+/// are kept, as they're written.
+///
+/// | Path                        | prefix      | root            | parent  | stem   | extension |
+/// |-----------------------------|-------------|-----------------|---------|--------|-----------|
+/// | `C:\dir\file.txt`           | `C:`        | `\`             | `dir\`  | `file` | `.txt`    |
+/// | `C:file.txt`                | `C:`        |                 |         | `file` | `.txt`    |
+/// | `\dir\`                     |             | `\`             | `dir\`  |        |           |
+/// | `\\server\share\file`       | `\\`        | `server\share\` |         | `file` |           |
+/// | `\\.\pipe\name`             | `\\.\`      | `pipe\`         |         | `name` |           |
+/// | `\\?\C:\.hidden`            | `\\?\`      | `C:\`           |         | `.hidden` |         |
+/// | `\\?\UNC\server\share\file` | `\\?\UNC\`  | `server\share\` |         | `file` |           |
+/// | `dir\..`                    |             |                 | `dir\..`|        |           |
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct WinRegions {
+    /// The part of the prefix that identifies the kind of path, such as `C:`,
+    /// `\\`, `\\.\` or `\\?\`. For a verbatim UNC path this is `\\?\UNC\`.
+    pub prefix: Option<Range<usize>>,
+    /// The rest of the root. This is the UNC server and share, the device
+    /// name or, for a verbatim path, the drive. It includes the separator
+    /// after it, which is all there is for drive and root relative paths.
+    ///
+    /// For an absolute path, the prefix and root together are the same as
+    /// [`WinPathExt::win_root`](crate::windows::WinPathExt::win_root).
+    pub root: Option<Range<usize>>,
+    /// The directories between the root and the file name, including the
+    /// separator before the file name. If the path has no file name (e.g. it
+    /// ends with a separator, `.` or `..`) then this runs to the end of the
+    /// path.
+    pub parent: Option<Range<usize>>,
+    /// The file name without its extension.
+    pub stem: Option<Range<usize>>,
+    /// The extension of the file name, including the `.`.
+    pub extension: Option<Range<usize>>,
+}
+
+/// Find the end of the `n`th component.
+fn component_end(path: &str, n: usize, is_sep: fn(char) -> bool) -> usize {
+    match path.match_indices(is_sep).nth(n - 1) {
+        Some((i, _)) => i,
+        None => path.len(),
+    }
+}
+
+impl AsRef<str> for WinUtf8Path {
+    fn as_ref(&self) -> &str {
+        &self.inner
+    }
+}
+impl AsRef<WinUtf8Path> for WinUtf8Path {
+    fn as_ref(&self) -> &WinUtf8Path {
+        self
+    }
+}
+impl AsRef<WinUtf8Path> for str {
+    fn as_ref(&self) -> &WinUtf8Path {
+        WinUtf8Path::from_utf8(self)
+    }
+}
+impl fmt::Display for WinUtf8Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+impl PartialEq<str> for WinUtf8Path {
+    fn eq(&self, other: &str) -> bool {
+        &self.inner == other
+    }
+}
+impl PartialEq<&str> for WinUtf8Path {
+    fn eq(&self, other: &&str) -> bool {
+        &self.inner == *other
+    }
+}
+impl ToOwned for WinUtf8Path {
+    type Owned = WinUtf8PathBuf;
+    fn to_owned(&self) -> WinUtf8PathBuf {
+        WinUtf8PathBuf { inner: self.inner.into() }
+    }
+}
+
+/// An owned Windows path stored as UTF-8.
+///
+/// This is the owned version of [`WinUtf8Path`].
+///
+/// # Example
+///
+/// ```
+/// use omnipath::windows::WinUtf8PathBuf;
+///
+/// let mut path = WinUtf8PathBuf::from(r"\\server\share");
+/// path.push("dir");
+/// path.push("file.txt");
+/// assert_eq!(path, r"\\server\share\dir\file.txt");
+/// path.push(r"\other.txt");
+/// assert_eq!(path, r"\\server\share\other.txt");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WinUtf8PathBuf {
+    inner: String,
+}
+impl WinUtf8PathBuf {
+    /// Create an empty path.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap a string as a Windows path.
+    pub fn from_utf8(path: String) -> Self {
+        Self { inner: path }
+    }
+
+    /// Borrow as a [`WinUtf8Path`].
+    pub fn as_path(&self) -> &WinUtf8Path {
+        WinUtf8Path::from_utf8(&self.inner)
+    }
+
+    /// Convert into a `String`.
+    pub fn into_string(self) -> String {
+        self.inner
+    }
+
+    /// Join a path onto this one.
+    ///
+    /// See [`WinUtf8Path::join`].
+    pub fn push(&mut self, path: &str) {
+        match WinPathKind::from_str(path) {
+            WinPathKind::CurrentDirectoryRelative => {
+                // `C:` is joined without a separator.
+                let is_drive_only = matches!(self.kind(), WinPathKind::DriveRelative(_))
+                    && self.root_len() == self.inner.len();
+                let needs_sep = !self.inner.is_empty()
+                    && !self.inner.ends_with(self.separator_fn())
+                    && !is_drive_only;
+                if needs_sep {
+                    self.inner.push('\\');
+                }
+                self.inner.push_str(path);
+            }
+            WinPathKind::RootRelative => {
+                let root_len = self.root_len();
+                self.inner.truncate(root_len);
+                self.inner.push_str(path);
+            }
+            _ => {
+                self.inner.clear();
+                self.inner.push_str(path);
+            }
+        }
+    }
+}
+impl Deref for WinUtf8PathBuf {
+    type Target = WinUtf8Path;
+    fn deref(&self) -> &WinUtf8Path {
+        self.as_path()
+    }
+}
+impl Borrow<WinUtf8Path> for WinUtf8PathBuf {
+    fn borrow(&self) -> &WinUtf8Path {
+        self.as_path()
+    }
+}
+impl AsRef<str> for WinUtf8PathBuf {
+    fn as_ref(&self) -> &str {
+        &self.inner
+    }
+}
+impl AsRef<WinUtf8Path> for WinUtf8PathBuf {
+    fn as_ref(&self) -> &WinUtf8Path {
+        self.as_path()
+    }
+}
+impl fmt::Display for WinUtf8PathBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+impl From<String> for WinUtf8PathBuf {
+    fn from(path: String) -> Self {
+        Self { inner: path }
+    }
+}
+impl From<&str> for WinUtf8PathBuf {
+    fn from(path: &str) -> Self {
+        Self { inner: path.into() }
+    }
+}
+impl From<WinUtf8PathBuf> for String {
+    fn from(path: WinUtf8PathBuf) -> Self {
+        path.inner
+    }
+}
+impl PartialEq<str> for WinUtf8PathBuf {
+    fn eq(&self, other: &str) -> bool {
+        self.inner == other
+    }
+}
+impl PartialEq<&str> for WinUtf8PathBuf {
+    fn eq(&self, other: &&str) -> bool {
+        self.inner == *other
+    }
+}
+impl PartialEq<WinUtf8Path> for WinUtf8PathBuf {
+    fn eq(&self, other: &WinUtf8Path) -> bool {
+        self.as_path() == other
+    }
+}
+impl PartialEq<WinUtf8PathBuf> for WinUtf8Path {
+    fn eq(&self, other: &WinUtf8PathBuf) -> bool {
+        self == other.as_path()
+    }
+}
@@ -0,0 +1,132 @@
+//! [Windows only] Scan an `OsStr` as UTF-16 without allocating.
+
+use std::ffi::OsStr;
+#[cfg(not(doc))]
+use std::os::windows::ffi::OsStrExt;
+
+use super::kind::WinPathKind;
+
+#[cfg(not(doc))]
+type EncodeWide<'a> = std::os::windows::ffi::EncodeWide<'a>;
+// The Windows extensions are not available when documenting on other platforms.
+#[cfg(doc)]
+type EncodeWide<'a> = core::iter::Copied<core::slice::Iter<'a, u16>>;
+
+/// [Windows only] Iterate over the UTF-16 code units of an `OsStr`.
+///
+/// Unlike collecting into a `Vec<u16>` this doesn't allocate. The returned
+/// iterator can be cheaply cloned so it can be scanned multiple times.
+///
+/// # Example
+///
+/// ```
+/// #[cfg(windows)]
+/// {
+///     use omnipath::windows::wide_chunks;
+///     use std::ffi::OsStr;
+///
+///     let chunks = wide_chunks(OsStr::new(r"C:\path\to\file.txt"));
+///     assert!(chunks.starts_with_units(&[b'C' as u16, b':' as u16]));
+///     assert!(chunks.ends_with_units(&[b't' as u16, b'x' as u16, b't' as u16]));
+///     assert_eq!(chunks.position_unit(b'\\' as u16), Some(2));
+///     assert_eq!(chunks.count(), 19);
+/// }
+/// ```
+pub fn wide_chunks(os: &OsStr) -> WideChunks<'_> {
+    WideChunks { inner: os.encode_wide() }
+}
+
+/// [Windows only] An iterator over the UTF-16 code units of an `OsStr`.
+///
+/// This is created by [`wide_chunks`].
+#[derive(Clone)]
+pub struct WideChunks<'a> {
+    inner: EncodeWide<'a>,
+}
+impl<'a> WideChunks<'a> {
+    /// Test if the remaining code units start with `units`.
+    pub fn starts_with_units(&self, units: &[u16]) -> bool {
+        let mut chunks = self.clone();
+        units.iter().all(|&unit| chunks.next() == Some(unit))
+    }
+
+    /// Test if the remaining code units end with `units`.
+    pub fn ends_with_units(&self, units: &[u16]) -> bool {
+        let len = self.clone().count();
+        match len.checked_sub(units.len()) {
+            Some(start) => self.clone().skip(start).eq(units.iter().copied()),
+            None => false,
+        }
+    }
+
+    /// Find the index of the first occurrence of `unit` in the remaining code units.
+    pub fn position_unit(&self, unit: u16) -> Option<usize> {
+        self.clone().position(|u| u == unit)
+    }
+}
+impl Iterator for WideChunks<'_> {
+    type Item = u16;
+    fn next(&mut self) -> Option<u16> {
+        self.inner.next()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// [Windows only] Find the [`WinPathKind`] of an `OsStr` without allocating.
+///
+/// This classifies paths in the same way as [`WinPathKind::from_str`] except
+/// that it also works for paths that are not valid Unicode.
+///
+/// # Example
+///
+/// ```
+/// #[cfg(windows)]
+/// {
+///     use omnipath::windows::{WideScanner, WinPathKind};
+///     use std::ffi::OsStr;
+///
+///     let scanner = WideScanner::new(OsStr::new(r"\\server\share\file.txt"));
+///     assert_eq!(scanner.kind(), WinPathKind::Unc);
+///
+///     let (kind, rest) = WideScanner::new(OsStr::new(r"C:\file.txt")).split();
+///     assert_eq!(kind, WinPathKind::Drive(b'C' as u16));
+///     assert_eq!(rest.count(), "file.txt".len());
+/// }
+/// ```
+#[derive(Clone)]
+pub struct WideScanner<'a> {
+    chunks: WideChunks<'a>,
+}
+impl<'a> WideScanner<'a> {
+    /// Create a scanner for the given `OsStr`.
+    pub fn new(os: &'a OsStr) -> Self {
+        Self { chunks: wide_chunks(os) }
+    }
+
+    /// Get the kind of path.
+    pub fn kind(&self) -> WinPathKind {
+        // The longest prefix is four code units long.
+        let mut units = [0; 4];
+        let mut len = 0;
+        for (slot, unit) in units.iter_mut().zip(self.chunks.clone()) {
+            *slot = unit;
+            len += 1;
+        }
+        WinPathKind::from_wide_prefix(&units[..len])
+    }
+
+    /// Split into the kind of path and the code units following the prefix.
+    ///
+    /// Like [`WinPathKind::split_str`], this only splits off the smallest part
+    /// needed to identify the path type.
+    pub fn split(self) -> (WinPathKind, WideChunks<'a>) {
+        let kind = self.kind();
+        let mut rest = self.chunks;
+        for _ in 0..kind.utf16_len() {
+            rest.next();
+        }
+        (kind, rest)
+    }
+}
@@ -1 +1,31 @@
+//! Helpers shared by the integration tests.
 
+/// Call `f` with every string of up to `max_len` characters made from `chars`,
+/// including the empty string.
+pub fn for_each_path(chars: &[char], max_len: usize, mut f: impl FnMut(&str)) {
+    let mut indices = Vec::with_capacity(max_len);
+    let mut path = String::new();
+    loop {
+        path.clear();
+        path.extend(indices.iter().map(|&i| chars[i]));
+        f(&path);
+
+        // Increment `indices` as if it were a number in base `chars.len()`.
+        let mut pos = 0;
+        loop {
+            if pos == indices.len() {
+                if indices.len() == max_len {
+                    return;
+                }
+                indices.push(0);
+                break;
+            }
+            indices[pos] += 1;
+            if indices[pos] < chars.len() {
+                break;
+            }
+            indices[pos] = 0;
+            pos += 1;
+        }
+    }
+}
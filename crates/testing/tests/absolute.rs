@@ -0,0 +1,179 @@
+//! Windows absolute paths that are longer than the initial buffer.
+#![cfg(windows)]
+use omnipath::windows::WinPathExt;
+use std::path::Path;
+
+fn long_path(len: usize) -> String {
+    let mut path = String::from(r"C:\");
+    while path.len() < len {
+        // Keep components short enough to be valid.
+        if path.len() % 200 == 0 {
+            path.push('\\');
+        } else {
+            path.push('a');
+        }
+    }
+    path
+}
+
+#[test]
+fn absolute_buffer_boundary() {
+    // The initial buffer holds 512 code units, including the null.
+    for len in 500..=520 {
+        let path = long_path(len);
+        assert_eq!(Path::new(&path).win_absolute().unwrap(), Path::new(&path), "{}", len);
+    }
+}
+
+#[test]
+fn absolute_long_paths() {
+    for len in [1000, 10_000, 32_000] {
+        let path = long_path(len);
+        assert_eq!(Path::new(&path).win_absolute().unwrap(), Path::new(&path), "{}", len);
+
+        let verbatim = Path::new(&path).to_verbatim().unwrap();
+        assert_eq!(verbatim.as_os_str().len(), len + r"\\?\".len(), "{}", len);
+    }
+}
+
+#[test]
+fn prepare_long_output_path() {
+    use omnipath::windows::PathProblem;
+
+    let root = Path::new(r"C:\output");
+    let rel = long_path(300);
+    let rel = rel.trim_start_matches(r"C:\");
+    let prepared = root.win_prepare_output_path(Path::new(rel)).unwrap();
+    assert!(prepared.made_verbatim);
+    assert_eq!(prepared.warnings, [PathProblem::LongPath]);
+    assert_eq!(prepared.display_path, root.join(rel));
+    let mut verbatim = std::ffi::OsString::from(r"\\?\");
+    verbatim.push(root.join(rel));
+    assert_eq!(prepared.os_path, verbatim);
+
+    // Only relative paths can be joined.
+    for rel in [r"C:\file", r"\file", r"\\server\share\file"] {
+        assert!(root.win_prepare_output_path(Path::new(rel)).is_err(), "{}", rel);
+    }
+    // `..` can't go above the root.
+    for rel in ["..", r"..\file", r"a\..\..\file", r"a\.\b\..\..\..\file"] {
+        assert!(root.win_prepare_output_path(Path::new(rel)).is_err(), "{}", rel);
+    }
+    let prepared = root.win_prepare_output_path(Path::new(r"a\b\..\..\file")).unwrap();
+    assert_eq!(prepared.os_path, Path::new(r"C:\output\file"));
+    // A drive relative path is a file name with a reserved character.
+    for rel in ["a:b", "C:file"] {
+        let prepared = root.win_prepare_output_path(Path::new(rel)).unwrap();
+        assert_eq!(prepared.os_path, Path::new(&format!(r"C:\output\{}", rel)), "{}", rel);
+        assert_eq!(prepared.warnings, [PathProblem::ReservedChar(rel.into())], "{}", rel);
+    }
+    // The root must be absolute.
+    assert!(Path::new("output").win_prepare_output_path(Path::new("file")).is_err());
+}
+
+#[test]
+fn clean_relative() {
+    use omnipath::windows::clean_utf8;
+
+    let tests = [
+        (r"path\.\from\..\to\\file..  ..", r"path\to\file"),
+        (r"..\..\x\.\y", r"..\..\x\y"),
+        // Only the last component loses all its trailing dots.
+        (r"...\..\x", "x"),
+        (r"a\...\..\..\x", "x"),
+        (r"a\...", r"a\"),
+        (r"..\...", r"..\"),
+    ];
+    for (path, expected) in tests {
+        assert_eq!(Path::new(path).win_clean().unwrap(), Path::new(expected), "{}", path);
+        assert_eq!(clean_utf8(path), expected, "{}", path);
+    }
+}
+
+#[test]
+fn verbatim_lexical_matches_os() {
+    use omnipath::windows::WinUtf8Path;
+
+    let paths = [
+        r"C:\",
+        r"C:\data\file.txt",
+        r"C:\data\dir\",
+        r"\\server\share",
+        r"\\server\share\dir\file.txt",
+        r"D:\a b\c.d.e",
+    ];
+    for path in paths {
+        let lexical = WinUtf8Path::from_utf8(path).to_verbatim_lexical().unwrap();
+        // `win_resolve_pair` always uses the OS.
+        let resolved = Path::new(path).win_resolve_pair().unwrap();
+        assert_eq!(Path::new(lexical.as_utf8()), resolved.for_os, "{}", path);
+        assert_eq!(
+            Path::new(lexical.as_utf8()),
+            Path::new(path).to_verbatim().unwrap(),
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn verbatim_exact_drive_relative() {
+    use omnipath::windows::{resolve_prefix, Win32Relative};
+
+    // Use a drive that isn't the current directory's drive.
+    let cwd = std::env::current_dir().unwrap();
+    let cwd_drive = cwd.to_str().and_then(|cwd| cwd.chars().next());
+    let drive = if cwd_drive == Some('Q') { 'R' } else { 'Q' };
+
+    let mut expected = std::ffi::OsString::from(r"\\?\");
+    expected.push(resolve_prefix(Win32Relative::DriveRelative(drive as u16)).unwrap());
+    assert!(Path::new(&expected).starts_with(format!(r"\\?\{drive}:\")));
+    assert_eq!(Path::new(&format!("{drive}:")).to_verbatim_exact().unwrap(), Path::new(&expected));
+
+    // The rest of the path is kept exactly.
+    let path = format!(r"{drive}:notes\..\file.txt.");
+    let mut expected = Path::new(&expected).as_os_str().to_owned();
+    if !expected.to_string_lossy().ends_with('\\') {
+        expected.push(r"\");
+    }
+    expected.push(r"notes\..\file.txt.");
+    assert_eq!(Path::new(&path).to_verbatim_exact().unwrap(), Path::new(&expected));
+}
+
+#[test]
+fn verbatim_if_needed() {
+    use std::borrow::Cow;
+
+    // Paths that don't need to be verbatim are borrowed.
+    for path in [r"C:\path\to\file.txt", r"path\..\file.txt", r"\\?\C:\path\..\nul"] {
+        let path = Path::new(path);
+        assert!(matches!(path.to_verbatim_if_needed().unwrap(), Cow::Borrowed(p) if p == path));
+    }
+    assert!(!Path::new(r"C:\path\..\file.txt").needs_verbatim().unwrap());
+
+    // Names are kept exactly but `.` and `..` are resolved.
+    let tests = [
+        (r"C:\path\.\to\..\file.txt.", r"\\?\C:\path\file.txt."),
+        (r"C:/path//nul", r"\\?\C:\path\nul"),
+        (r"\\server\share\dir\..\aux.txt", r"\\?\UNC\server\share\aux.txt"),
+    ];
+    for (path, expected) in tests {
+        assert!(Path::new(path).needs_verbatim().unwrap(), "{:?}", path);
+        assert_eq!(Path::new(path).to_verbatim_if_needed().unwrap(), Path::new(expected));
+    }
+
+    // Relative paths are joined to the current directory.
+    let cwd = std::env::current_dir().unwrap();
+    let mut expected = std::ffi::OsString::from(r"\\?\");
+    expected.push(cwd.join("file."));
+    assert_eq!(Path::new(r"dir\..\file.").to_verbatim_if_needed().unwrap(), Path::new(&expected));
+
+    // The length is checked after the path is made absolute.
+    let name = "a".repeat(250);
+    assert!(Path::new(&name).needs_verbatim().unwrap());
+    let mut expected = std::ffi::OsString::from(r"\\?\");
+    expected.push(cwd.join(&name));
+    assert_eq!(Path::new(&name).to_verbatim_if_needed().unwrap(), Path::new(&expected));
+
+    assert!(Path::new("").needs_verbatim().is_err());
+}
@@ -0,0 +1,118 @@
+use omnipath::AbsolutePathBuf;
+use std::path::Path;
+
+fn resolve(path: &str) -> AbsolutePathBuf {
+    AbsolutePathBuf::resolve(Path::new(path)).unwrap()
+}
+
+#[test]
+fn resolve_relative() {
+    let path = resolve("path/to/file");
+    assert!(path.is_absolute());
+    assert_eq!(path.as_path(), omnipath::sys_absolute(Path::new("path/to/file")).unwrap());
+    assert!(AbsolutePathBuf::resolve(Path::new("")).is_err());
+}
+
+#[test]
+fn trusted() {
+    assert!(AbsolutePathBuf::try_from_trusted("relative".into()).is_err());
+    let path = resolve("path/to/file");
+    let trusted = AbsolutePathBuf::try_from_trusted(path.clone().into_path_buf()).unwrap();
+    assert_eq!(path, trusted);
+}
+
+#[cfg(unix)]
+#[test]
+fn push_posix() {
+    let mut path = resolve("/a/b");
+    path.push("c/./d".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new("/a/b/c/d"));
+    path.push("../../e".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new("/a/b/e"));
+
+    // Pushing an absolute path is an error.
+    assert!(path.push("/etc".as_ref()).is_err());
+    assert_eq!(path.as_path(), Path::new("/a/b/e"));
+
+    // Going above the root is an error unless it's clamped.
+    let mut path = resolve("/a");
+    assert!(path.push("../../b".as_ref()).is_err());
+    assert_eq!(path.as_path(), Path::new("/a"));
+    path.push_clamped("../../b".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new("/b"));
+
+    // `..` that is kept after a (possible) symlink isn't removed.
+    let mut path = resolve("/a/..");
+    path.push("..".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new("/a/../.."));
+    // Nor is it popped.
+    assert!(path.pop());
+    assert_eq!(path.as_path(), Path::new("/a/../../.."));
+
+    let mut path = resolve("/a/b");
+    assert!(path.pop());
+    assert_eq!(path.as_path(), Path::new("/a"));
+    assert!(path.pop());
+    assert!(!path.pop());
+    assert_eq!(path.as_path(), Path::new("/"));
+}
+
+#[cfg(unix)]
+#[test]
+fn set_file_name_posix() {
+    let mut path = resolve("/a/b");
+    path.set_file_name("c".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new("/a/c"));
+    for name in ["", ".", "..", "x/y", "/x"] {
+        assert!(path.set_file_name(name.as_ref()).is_err(), "{:?}", name);
+    }
+    assert!(resolve("/").set_file_name("c".as_ref()).is_err());
+}
+
+#[cfg(windows)]
+#[test]
+fn push_windows() {
+    let mut path = resolve(r"C:\a\b");
+    path.push(r"c/.\d".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new(r"C:\a\b\c\d"));
+    path.push(r"..\..\e".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new(r"C:\a\b\e"));
+
+    // Names that normalizing would change are an error.
+    for pushed in ["x.", "x ", r"x.\y", "NUL", r"nul.txt\y", "COM1", r"x\C:y", "a:b"] {
+        assert!(path.push(pushed.as_ref()).is_err(), "{:?}", pushed);
+    }
+
+    // Anything with a root or prefix is an error.
+    for pushed in [r"C:\x", r"\x", r"C:x", r"\\server\share\x", r"\\?\C:\x"] {
+        assert!(path.push(pushed.as_ref()).is_err(), "{:?}", pushed);
+    }
+    assert_eq!(path.as_path(), Path::new(r"C:\a\b\e"));
+
+    let mut path = resolve(r"C:\a");
+    assert!(path.push(r"..\..\b".as_ref()).is_err());
+    path.push_clamped(r"..\..\b".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new(r"C:\b"));
+}
+
+#[cfg(windows)]
+#[test]
+fn push_verbatim() {
+    let mut path = resolve(r"\\?\C:\a");
+    // `/` in the pushed path is a separator and `..` is resolved.
+    path.push(r"b/c\..\d".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new(r"\\?\C:\a\b\d"));
+    assert!(path.push(r"..\..\..\..".as_ref()).is_err());
+}
+
+#[cfg(windows)]
+#[test]
+fn set_file_name_windows() {
+    let mut path = resolve(r"C:\a\b");
+    path.set_file_name("c".as_ref()).unwrap();
+    assert_eq!(path.as_path(), Path::new(r"C:\a\c"));
+    for name in ["", ".", "..", r"x\y", "x/y", r"\x", "C:x", "e.", "e ", "NUL", "con.txt", "a:b"] {
+        assert!(path.set_file_name(name.as_ref()).is_err(), "{:?}", name);
+    }
+    assert!(resolve(r"C:\").set_file_name("c".as_ref()).is_err());
+}
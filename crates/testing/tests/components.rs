@@ -0,0 +1,69 @@
+use omnipath::{sys_components, SysComponent};
+use std::path::{Component, Path};
+
+fn check_round_trip(path: &str) {
+    let path = Path::new(path);
+    let components: Vec<Component> = sys_components(path).map(Component::from).collect();
+    assert_eq!(components, path.components().collect::<Vec<_>>(), "{:?}", path);
+
+    let reversed: Vec<SysComponent> = sys_components(path).rev().collect();
+    let mut forward: Vec<SysComponent> = sys_components(path).collect();
+    forward.reverse();
+    assert_eq!(reversed, forward, "{:?}", path);
+}
+
+#[test]
+fn components_round_trip() {
+    for path in ["", ".", "..", "a/./b/../c", "/a/b/", "//a/b", "///a"] {
+        check_round_trip(path);
+    }
+    #[cfg(windows)]
+    for path in [r"C:\a", r"C:a", r"\a", r"\\server\share\a", r"\\?\C:\a", r"\\.\pipe\a"] {
+        check_round_trip(path);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn posix_roots() {
+    let root = |path: &str| match sys_components(Path::new(path)).next() {
+        Some(SysComponent::Root(root)) => {
+            Some((root.as_os_str().to_owned(), root.is_double_slash()))
+        }
+        _ => None,
+    };
+    assert_eq!(root("/a"), Some(("/".into(), false)));
+    assert_eq!(root("//a"), Some(("//".into(), true)));
+    assert_eq!(root("///a"), Some(("/".into(), false)));
+    assert_eq!(root("a"), None);
+
+    // The double slash is also seen from the back.
+    let last = sys_components(Path::new("//")).next_back();
+    assert!(matches!(last, Some(SysComponent::Root(root)) if root.is_double_slash()));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_roots() {
+    fn roots(path: &str) -> Vec<SysComponent<'_>> {
+        sys_components(Path::new(path))
+            .filter(|component| matches!(component, SysComponent::Root(_)))
+            .collect()
+    }
+    match roots(r"\\?\UNC\server\share\a")[..] {
+        [SysComponent::Root(prefix), SysComponent::Root(sep)] => {
+            assert_eq!(prefix.unc(), Some(("server".as_ref(), "share".as_ref())));
+            assert!(prefix.is_verbatim());
+            assert!(sep.is_separator());
+        }
+        ref roots => panic!("{:?}", roots),
+    }
+    match roots(r"C:a")[..] {
+        [SysComponent::Root(prefix)] => assert_eq!(prefix.drive(), Some(b'C')),
+        ref roots => panic!("{:?}", roots),
+    }
+    match roots(r"\\.\pipe\a")[..] {
+        [SysComponent::Root(prefix), _] => assert_eq!(prefix.device(), Some("pipe".as_ref())),
+        ref roots => panic!("{:?}", roots),
+    }
+}
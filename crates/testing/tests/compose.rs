@@ -0,0 +1,68 @@
+use omnipath::windows::{compose, ComposeError, WinPathKind};
+
+#[test]
+fn test_compose_round_trip() {
+    let kinds = [
+        (WinPathKind::Drive(b'C' as u16), None),
+        (WinPathKind::Drive('£' as u16), None),
+        (WinPathKind::Drive('三' as u16), None),
+        (WinPathKind::DriveRelative(b'C' as u16), None),
+        (WinPathKind::DriveRelative('£' as u16), None),
+        (WinPathKind::Unc, Some(("server", "share"))),
+        (WinPathKind::Device, None),
+        (WinPathKind::Verbatim, None),
+        (WinPathKind::RootRelative, None),
+        (WinPathKind::CurrentDirectoryRelative, None),
+    ];
+    let components: &[&[&str]] = &[&[], &["file.txt"], &["dir", "file.txt"], &["..", ".", "三"]];
+    for (kind, unc) in kinds {
+        for components in components {
+            let path = compose(kind, unc, components).unwrap();
+            let (parsed, rest) = WinPathKind::split_str(&path);
+            assert_eq!(parsed, kind, "{path}");
+
+            let mut expected: Vec<&str> = Vec::new();
+            if let Some((server, share)) = unc {
+                expected.extend([server, share]);
+            }
+            expected.extend(components.iter());
+            let rest: Vec<&str> =
+                if rest.is_empty() { Vec::new() } else { rest.split('\\').collect() };
+            assert_eq!(rest, expected, "{path}");
+        }
+    }
+}
+
+type Unc<'a> = Option<(&'a str, &'a str)>;
+
+#[test]
+fn test_compose_errors() {
+    use ComposeError::*;
+    let tests: &[(WinPathKind, Unc, &[&str], ComposeError)] = &[
+        (WinPathKind::Unc, None, &["file"], MissingServerShare),
+        (WinPathKind::Drive(b'C' as u16), Some(("server", "share")), &[], UnexpectedServerShare),
+        (WinPathKind::Unc, Some(("", "share")), &[], InvalidComponent),
+        (WinPathKind::Unc, Some(("server", r"sh\are")), &[], InvalidComponent),
+        (WinPathKind::Drive(b'C' as u16), None, &["dir", ""], InvalidComponent),
+        (WinPathKind::Drive(b'C' as u16), None, &["dir/file"], InvalidComponent),
+        (WinPathKind::CurrentDirectoryRelative, None, &["C:"], KindMismatch),
+        (WinPathKind::Drive(0xD800), None, &[], KindMismatch),
+    ];
+    for &(kind, unc, components, error) in tests {
+        assert_eq!(compose(kind, unc, components), Err(error));
+    }
+}
+
+#[test]
+fn test_compose_canonical_device() {
+    // Composing a device path always uses the `\\.\` spelling.
+    assert_eq!(compose(WinPathKind::Device, None, &["pipe", "x"]).unwrap(), r"\\.\pipe\x");
+    let mut prefix = String::new();
+    WinPathKind::Device.write_prefix(&mut prefix);
+    assert_eq!(prefix, r"\\.\");
+    // Even when the kind was parsed from the `\\?\` spelling.
+    let (kind, rest) = WinPathKind::split_str("//?/pipe/x");
+    assert_eq!(kind, WinPathKind::Device);
+    let components: Vec<&str> = rest.split('/').collect();
+    assert_eq!(compose(kind, None, &components).unwrap(), r"\\.\pipe\x");
+}
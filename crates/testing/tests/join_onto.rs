@@ -0,0 +1,42 @@
+use omnipath::windows::WinPathKind;
+use omnipath::{join_onto, JoinRejected};
+use std::path::Path;
+
+#[test]
+fn join_onto_rejects() {
+    let base = Path::new("base");
+    let tests = [
+        ("/etc/passwd", JoinRejected::NotRelative(WinPathKind::RootRelative)),
+        (r"C:\evil", JoinRejected::NotRelative(WinPathKind::Drive(b'C' as u16))),
+        ("C:evil", JoinRejected::NotRelative(WinPathKind::DriveRelative(b'C' as u16))),
+        // A component with a prefix would replace the base on Windows.
+        ("a/C:x", JoinRejected::NotRelative(WinPathKind::DriveRelative(b'C' as u16))),
+        ("a/./b/C:", JoinRejected::NotRelative(WinPathKind::DriveRelative(b'C' as u16))),
+        ("..", JoinRejected::EscapesBase),
+        ("a/../../evil", JoinRejected::EscapesBase),
+    ];
+    for (entry, expected) in tests {
+        assert_eq!(join_onto(base, Path::new(entry)), Err(expected), "{:?}", entry);
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn join_onto_rejects_windows() {
+    let base = Path::new("base");
+    let tests = [
+        (r"a\C:\x", JoinRejected::NotRelative(WinPathKind::DriveRelative(b'C' as u16))),
+        (r"a\..\..\x", JoinRejected::EscapesBase),
+    ];
+    for (entry, expected) in tests {
+        assert_eq!(join_onto(base, Path::new(entry)), Err(expected), "{:?}", entry);
+    }
+}
+
+#[test]
+fn join_onto_joins() {
+    let base = Path::new("base");
+    let joined = join_onto(base, Path::new("a/./b/../c")).unwrap();
+    assert_eq!(joined, base.join("a").join("c"));
+    assert_eq!(join_onto(base, Path::new("a/..")).unwrap(), base);
+}
@@ -1,5 +1,29 @@
 use omnipath::windows::WinPathKind::{self, *};
 
+// Classification must work at compile time.
+const _: () = {
+    assert!(matches!(WinPathKind::from_str(r"\\?\C:\file.txt"), Verbatim));
+    assert!(matches!(WinPathKind::from_str(r"C:\file.txt"), Drive(0x43)));
+    assert!(WinPathKind::split_str(r"\\server\share").1.len() == r"server\share".len());
+    assert!(omnipath::windows::verbatim_has_forward_slash(r"\\?\C:/file.txt"));
+};
+
+#[test]
+fn test_mixed_separators() {
+    use omnipath::windows::has_mixed_separators;
+
+    assert!(has_mixed_separators(r"a\b/c"));
+    assert!(!has_mixed_separators(r"a\b\c"));
+    assert!(!has_mixed_separators("a/b/c"));
+    assert!(!has_mixed_separators("file.txt"));
+    assert!(has_mixed_separators(r"//server\share"));
+    assert!(has_mixed_separators(r"\\./pipe\name"));
+    // `/` isn't a separator in a verbatim path.
+    assert!(!has_mixed_separators(r"\\?\C:\a/b"));
+    // But `\\?/` isn't a verbatim prefix.
+    assert!(has_mixed_separators(r"\\?/C:\a"));
+}
+
 #[test]
 fn test_kinds() {
     for (input, kind) in DATA {
@@ -7,6 +31,27 @@ fn test_kinds() {
     }
 }
 
+#[test]
+fn test_os_str_kinds() {
+    use std::ffi::OsStr;
+    for (input, kind) in DATA {
+        assert_eq!(kind, &WinPathKind::from_os_str(OsStr::new(input)), "{:?}", input);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_non_utf8_kinds() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    let kind = |bytes: &[u8]| WinPathKind::from_os_str(OsStr::from_bytes(bytes));
+    assert_eq!(kind(b"\\\\?\\\xFF"), Verbatim);
+    assert_eq!(kind(b"C:\\\xFF"), Drive(b'C' as u16));
+    assert_eq!(kind(b"C:\xFF"), DriveRelative(b'C' as u16));
+    assert_eq!(kind(b"\\\\\xFF"), Unc);
+    assert_eq!(kind(b"\xFF:\\"), CurrentDirectoryRelative);
+}
+
 // This could probably be compressed so it doesn't take up so much space.
 static DATA: &[(&str, WinPathKind)] = &[
     ("", CurrentDirectoryRelative),
@@ -66440,3 +66485,66 @@ static DATA: &[(&str, WinPathKind)] = &[
     ("三😍😍😍😍", CurrentDirectoryRelative),
     ("😍😍😍😍😍", CurrentDirectoryRelative),
 ];
+
+#[test]
+fn test_verbatim_str() {
+    use omnipath::windows::{VerbatimParseError, VerbatimStr, Win32Absolute};
+
+    let tests = [
+        (r"\\?\C:\file", Ok((Win32Absolute::Drive(b'C' as u16), r"C:\file"))),
+        (r"\\?\C:", Ok((Win32Absolute::Device, "C:"))),
+        (r"\\?\UNC\server\share", Ok((Win32Absolute::Unc, r"\server\share"))),
+        (r"\\?\unc", Ok((Win32Absolute::Unc, ""))),
+        (r"\\?\pipe\name", Ok((Win32Absolute::Device, r"pipe\name"))),
+        (r"\\?\", Err(VerbatimParseError::EmptyPath)),
+        (r"\\?", Err(VerbatimParseError::NotVerbatim)),
+        (r"//?/C:\file", Err(VerbatimParseError::NotVerbatim)),
+        (r"\\.\pipe\name", Err(VerbatimParseError::NotVerbatim)),
+        (r"C:\file", Err(VerbatimParseError::NotVerbatim)),
+        ("", Err(VerbatimParseError::NotVerbatim)),
+    ];
+    for (path, expected) in tests {
+        let parsed = VerbatimStr::new(path);
+        assert_eq!(parsed.map(|v| v.win32_parts()), expected, "{:?}", path);
+        assert_eq!(Win32Absolute::from_verbatim_str(path), expected, "{:?}", path);
+        if let Ok(verbatim) = parsed {
+            assert_eq!(verbatim.subpath(), &path[4..]);
+            assert_eq!(Ok(verbatim.win32_kind()), expected.map(|(kind, _)| kind));
+        }
+    }
+}
+
+#[test]
+fn test_verbatim_kind() {
+    use omnipath::windows::{VerbatimStr, WinVerbatimKind};
+
+    const GUID: &str = "26a21bda-a627-11d7-9931-806e6f6e6963";
+    let tests = [
+        (r"\\?\C:\file".to_string(), WinVerbatimKind::Drive(b'C' as u16)),
+        (r"\\?\UNC\server\share".to_string(), WinVerbatimKind::Unc),
+        (r"\\?\unc".to_string(), WinVerbatimKind::Unc),
+        (format!(r"\\?\Volume{{{}}}\dir\file", GUID), WinVerbatimKind::VolumeGuid),
+        (format!(r"\\?\Volume{{{}}}", GUID), WinVerbatimKind::VolumeGuid),
+        (format!(r"\\?\volume{{{}}}\", GUID.to_uppercase()), WinVerbatimKind::VolumeGuid),
+        (r"\\?\GLOBALROOT\Device\HarddiskVolume3\file".to_string(), WinVerbatimKind::GlobalRoot),
+        (r"\\?\GlobalRoot".to_string(), WinVerbatimKind::GlobalRoot),
+        (r"\\?\pipe\name".to_string(), WinVerbatimKind::Pipe),
+        (r"\\?\PIPE\".to_string(), WinVerbatimKind::Pipe),
+        (r"\\?\C:".to_string(), WinVerbatimKind::Device),
+        (r"\\?\C:file".to_string(), WinVerbatimKind::Other),
+        (r"\\?\NUL".to_string(), WinVerbatimKind::Device),
+        (r"\\?\com1".to_string(), WinVerbatimKind::Device),
+        (r"\\?\nul.txt".to_string(), WinVerbatimKind::Other),
+        (r"\\?\HarddiskVolume3\file".to_string(), WinVerbatimKind::Other),
+        // Not a GUID.
+        (r"\\?\Volume{26a21bda}\file".to_string(), WinVerbatimKind::Other),
+        (format!(r"\\?\Volume{{{}}}", GUID.replace('-', "_")), WinVerbatimKind::Other),
+        (format!(r"\\?\Volume{{{}}}x", GUID), WinVerbatimKind::Other),
+        // Only `\` is a separator in verbatim paths.
+        (r"\\?\pipe/name".to_string(), WinVerbatimKind::Other),
+    ];
+    for (path, expected) in tests {
+        let verbatim = VerbatimStr::new(&path).unwrap();
+        assert_eq!(verbatim.verbatim_kind(), expected, "{:?}", path);
+    }
+}
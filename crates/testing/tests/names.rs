@@ -0,0 +1,217 @@
+use omnipath::windows::{has_reserved_device_name, is_reserved_device_name, sanitize_component};
+use std::path::Path;
+
+#[test]
+fn test_sanitize_component() {
+    for (input, expected) in SANITIZE {
+        assert_eq!(sanitize_component(input, '_'), *expected, "{:?}", input);
+        // Sanitizing is idempotent.
+        assert_eq!(sanitize_component(expected, '_'), *expected, "{:?}", expected);
+    }
+}
+
+#[test]
+fn test_sanitize_replacement() {
+    assert_eq!(sanitize_component("a/b", '-'), "a-b");
+    // Replacements that aren't allowed in a file name fall back to `_`.
+    assert_eq!(sanitize_component("a/b", '\\'), "a_b");
+    assert_eq!(sanitize_component("file.", '.'), "file_");
+    assert_eq!(sanitize_component("file ", ' '), "file_");
+}
+
+static SANITIZE: &[(&str, &str)] = &[
+    ("file.txt", "file.txt"),
+    ("", "_"),
+    // Illegal characters.
+    (r#"a<b>c:d"e/f\g|h?i*j"#, "a_b_c_d_e_f_g_h_i_j"),
+    ("tab\there", "tab_here"),
+    ("nul\0byte", "nul_byte"),
+    // Trailing dots and spaces.
+    ("file.", "file_"),
+    ("file. .", "file___"),
+    (".", "_"),
+    ("..", "__"),
+    (".hidden", ".hidden"),
+    // Reserved device names.
+    ("NUL", "NUL_"),
+    ("con", "con_"),
+    ("com1.txt", "com1_.txt"),
+    ("LPT9.tar.gz", "LPT9_.tar.gz"),
+    ("aux:stream", "aux_stream"),
+    ("COM0", "COM0"),
+    ("console", "console"),
+];
+
+#[cfg(unix)]
+#[test]
+fn test_sanitize_component_posix() {
+    use omnipath::posix::sanitize_component_posix;
+
+    let tests = [
+        ("file.txt", "file.txt"),
+        ("", "_"),
+        (".", "_"),
+        ("..", "__"),
+        ("...", "..."),
+        (".hidden", ".hidden"),
+        ("a/b", "a_b"),
+        ("/a/b/", "_a_b_"),
+        ("a\0b", "a_b"),
+        ("\0", "_"),
+        // Only `/` and null are reserved.
+        (r#"a<b>c:d"e\f|g?h*i"#, r#"a<b>c:d"e\f|g?h*i"#),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(sanitize_component_posix(input, '_'), expected, "{:?}", input);
+    }
+    assert_eq!(sanitize_component_posix("a/b", '/'), "a_b");
+    assert_eq!(sanitize_component_posix("a/b", '\0'), "a_b");
+    // A `.` replacement could create `.` or `..`.
+    for (input, expected) in [(".", "_"), ("..", "__"), ("", "_"), ("./", "._"), ("a/b", "a_b")] {
+        assert_eq!(sanitize_component_posix(input, '.'), expected, "{:?}", input);
+    }
+}
+
+#[test]
+fn test_reserved_device_names() {
+    for name in RESERVED {
+        assert!(is_reserved_device_name(name), "{:?}", name);
+    }
+    for name in NOT_RESERVED {
+        assert!(!is_reserved_device_name(name), "{:?}", name);
+    }
+}
+
+#[test]
+fn test_has_reserved_device_name() {
+    let reserved = [
+        "nul",
+        r"C:\path\to\nul.txt",
+        "path/to/aux",
+        "C:nul",
+        "c:CON.txt",
+        r"C:path\com1",
+        r"\\server\share\prn",
+        r"\\.\nul",
+    ];
+    for path in reserved {
+        assert!(has_reserved_device_name(Path::new(path)), "{:?}", path);
+    }
+    let not_reserved = ["", "C:", r"C:\", r"C:\con\file.txt", "C:file", r"nul\file"];
+    for path in not_reserved {
+        assert!(!has_reserved_device_name(Path::new(path)), "{:?}", path);
+    }
+}
+
+static RESERVED: &[&str] = &[
+    "CON",
+    "con",
+    "Prn",
+    "aux",
+    "NUL",
+    "NUL.txt",
+    "nul.tar.gz",
+    "nul.",
+    "NUL  ",
+    "com1 .log",
+    "COM9",
+    "lpt1",
+    "LPT9",
+    "COM\u{b9}",
+    "LPT\u{b2}.txt",
+    "com\u{b3}",
+    "CON:stream",
+    "CONIN$",
+    "conout$.txt",
+];
+
+static NOT_RESERVED: &[&str] = &[
+    "",
+    "CONSOLE",
+    "console.txt",
+    "COM0",
+    "LPT0",
+    "COM10",
+    "LPT",
+    "COM\u{b4}",
+    "COM\u{2074}",
+    ".nul",
+    "file.nul",
+    "CONOUT",
+    "CONIN$$",
+    " NUL",
+];
+
+#[test]
+fn test_sanitize_portable() {
+    use omnipath::sanitize_portable;
+
+    let tests = [
+        // Valid on POSIX but not on Windows.
+        ("a:b", "a_b"),
+        (r"a\b", "a_b"),
+        ("what?", "what_"),
+        ("file.", "file_"),
+        ("file ", "file_"),
+        ("con", "con_"),
+        ("lpt1.txt", "lpt1_.txt"),
+        // Valid on neither.
+        ("a/b", "a_b"),
+        ("a\0b", "a_b"),
+        ("", "_"),
+        (".", "_"),
+        ("..", "__"),
+        // Valid on both.
+        (".hidden", ".hidden"),
+        ("file.tar.gz", "file.tar.gz"),
+    ];
+    for (input, expected) in tests {
+        assert_eq!(sanitize_portable(input, '_'), expected, "{:?}", input);
+        #[cfg(unix)]
+        assert_eq!(
+            omnipath::posix::sanitize_component_posix(expected, '_'),
+            expected,
+            "{:?}",
+            expected
+        );
+        assert_eq!(sanitize_component(expected, '_'), expected, "{:?}", expected);
+    }
+}
+
+#[test]
+fn test_is_valid_filename() {
+    use omnipath::{is_valid_filename, Platform};
+
+    // (name, Windows, POSIX)
+    let tests = [
+        ("file.txt", true, true),
+        (".hidden", true, true),
+        ("...a", true, true),
+        ("", false, false),
+        (".", false, false),
+        ("..", false, false),
+        ("a/b", false, false),
+        ("a\0b", false, false),
+        ("a:b", false, true),
+        (r"a\b", false, true),
+        ("tab\there", false, true),
+        ("what?", false, true),
+        ("file.", false, true),
+        ("file ", false, true),
+        ("con", false, true),
+        ("COM1.log", false, true),
+        ("CONOUT$", false, true),
+        ("console", true, true),
+    ];
+    for (name, windows, posix) in tests {
+        assert_eq!(is_valid_filename(name, Platform::Windows), windows, "{:?}", name);
+        assert_eq!(is_valid_filename(name, Platform::Posix), posix, "{:?}", name);
+        assert_eq!(is_valid_filename(name, Platform::Portable), windows && posix, "{:?}", name);
+    }
+
+    // Valid names are the ones that sanitizing leaves unchanged.
+    for (name, ..) in tests {
+        let sanitized = omnipath::sanitize_portable(name, '_');
+        assert_eq!(is_valid_filename(name, Platform::Portable), sanitized == name, "{:?}", name);
+    }
+}
@@ -0,0 +1,90 @@
+use omnipath::{needs_normalization, sys_absolute};
+use std::path::Path;
+use testing::for_each_path;
+
+// Every path for which `needs_normalization` returns `false` must be unchanged
+// by `sys_absolute`.
+#[test]
+fn test_needs_normalization() {
+    const CHARS: &[char] = &['a', '.', ' ', '/', '\\', ':'];
+    #[cfg(unix)]
+    const PREFIXES: &[&str] = &["/", "//", ""];
+    #[cfg(windows)]
+    const PREFIXES: &[&str] = &[r"C:\", r"c:\", r"\\", r"\\server\share\", r"\\?\C:\", r"\\.\", ""];
+
+    let mut checked = 0;
+    let mut path = String::new();
+    for prefix in PREFIXES {
+        for_each_path(CHARS, 6, |rest| {
+            path.clear();
+            path.push_str(prefix);
+            path.push_str(rest);
+            check(Path::new(&path));
+            checked += 1;
+        });
+    }
+    assert!(checked > 0);
+}
+
+fn check(path: &Path) {
+    if !needs_normalization(path) {
+        let absolute = sys_absolute(path).unwrap();
+        assert_eq!(absolute.as_os_str(), path.as_os_str());
+    }
+}
+
+#[test]
+fn test_absolute_empty_path() {
+    let error = sys_absolute(Path::new("")).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+
+    #[cfg(unix)]
+    {
+        use omnipath::posix::PosixPathExt;
+        let empty = Path::new("");
+        assert!(empty.posix_absolute().is_err());
+        assert!(empty.posix_lexically_absolute().is_err());
+        assert!(empty.posix_absolute_from("/tmp".as_ref()).is_err());
+        assert!(empty.posix_lexically_absolute_from("/tmp".as_ref()).is_err());
+    }
+    #[cfg(windows)]
+    {
+        use omnipath::windows::WinPathExt;
+        let empty = Path::new("");
+        assert!(empty.win_absolute().is_err());
+        assert!(empty.win_absolute_from(r"C:\".as_ref()).is_err());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_posix_is_normal() {
+    use omnipath::posix::{posix_is_normal, PosixPathExt};
+
+    let normal =
+        ["/", "//", "/a", "/a/", "//a/b", "a", "a/b/", "..", "../../a", ".", ".a", "a..", "/..."];
+    let not_normal = [
+        "", "./a", "a/.", "a//b", "///a", "/./a", "/..", "//..", "a/..", "../a/..", "a/../b",
+        "//a//",
+    ];
+    for path in normal {
+        assert!(posix_is_normal(Path::new(path)), "{:?}", path);
+    }
+    for path in not_normal {
+        assert!(!posix_is_normal(Path::new(path)), "{:?}", path);
+    }
+
+    // Absolute paths are normal if `posix_lexically_absolute` doesn't change them.
+    const CHARS: &[char] = &['a', '.', '/'];
+    let mut path = String::new();
+    for prefix in ["/", "//"] {
+        for_each_path(CHARS, 6, |rest| {
+            path.clear();
+            path.push_str(prefix);
+            path.push_str(rest);
+            let lexical = Path::new(&path).posix_lexically_absolute().unwrap();
+            let is_unchanged = lexical.as_os_str() == path.as_str();
+            assert_eq!(posix_is_normal(Path::new(&path)), is_unchanged, "{:?}", path);
+        });
+    }
+}
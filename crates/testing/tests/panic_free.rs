@@ -0,0 +1,91 @@
+//! Parsing must never panic, even on short or malformed paths.
+//!
+//! This tries every string of up to five characters made from characters that
+//! are significant to path parsing (plus multi-byte characters to test UTF-8
+//! handling).
+use omnipath::windows::{self, WinPathKind};
+
+const CHARS: &[char] = &['\\', '/', '.', '?', ':', ' ', 'C', 'U', 'N', '£', '三', '😍'];
+const MAX_LEN: usize = 5;
+
+fn for_each_path(f: impl FnMut(&str)) {
+    testing::for_each_path(CHARS, MAX_LEN, f);
+}
+
+#[test]
+fn kind_never_panics() {
+    let mut prefix = String::new();
+    for_each_path(|path| {
+        let kind = WinPathKind::from_str(path);
+        let (split_kind, rest) = WinPathKind::split_str(path);
+        assert_eq!(kind, split_kind, "{:?}", path);
+        assert_eq!(kind, WinPathKind::from_os_str(path.as_ref()), "{:?}", path);
+        assert!(path.ends_with(rest), "{:?}", path);
+        assert_eq!(kind.utf8_len(), path.len() - rest.len(), "{:?}", path);
+
+        prefix.clear();
+        kind.write_prefix(&mut prefix);
+        windows::verbatim_has_forward_slash(path);
+        windows::has_mixed_separators(path);
+        windows::to_shell_display(path);
+        let _ = windows::VerbatimStr::new(path).map(|v| (v.win32_parts(), v.verbatim_kind()));
+        windows::device_path_kind(path);
+        windows::same_unc_share(path, path);
+        let is_clean = matches!(windows::clean_utf8(path), std::borrow::Cow::Borrowed(_));
+        assert_eq!(windows::is_win_clean(path), is_clean, "{:?}", path);
+    });
+}
+
+#[test]
+fn names_never_panic() {
+    for_each_path(|path| {
+        windows::looks_like_short_name(path);
+        windows::sanitize_component(path, '_');
+        windows::is_reserved_device_name(path);
+        let utf8 = windows::WinUtf8Path::from_utf8(path);
+        utf8.parent();
+        utf8.file_name();
+        utf8.unc_server();
+        utf8.unc_share();
+        utf8.needs_verbatim();
+        // The regions cover the whole path, in order.
+        let regions = utf8.regions();
+        let mut end = 0;
+        for region in
+            [regions.prefix, regions.root, regions.parent, regions.stem, regions.extension]
+                .into_iter()
+                .flatten()
+        {
+            assert_eq!(region.start, end, "{:?}", path);
+            assert!(region.end > region.start && path.get(region.clone()).is_some(), "{:?}", path);
+            end = region.end;
+        }
+        assert_eq!(end, path.len(), "{:?}", path);
+        utf8.join(path);
+        for max_chars in 0..=MAX_LEN {
+            let truncated = windows::truncate_for_log(path, max_chars);
+            assert!(truncated.chars().count() <= max_chars, "{:?}", path);
+        }
+    });
+}
+
+#[test]
+fn compose_never_panics() {
+    for_each_path(|path| {
+        let kind = WinPathKind::from_str(path);
+        let _ = windows::compose(kind, None, &[path]);
+        let _ = windows::compose(kind, Some((path, path)), &[path, path]);
+    });
+}
+
+#[test]
+fn path_helpers_never_panic() {
+    use std::path::Path;
+
+    for_each_path(|path| {
+        let _ = omnipath::join_onto(Path::new("base"), Path::new(path));
+        omnipath::needs_normalization(Path::new(path));
+        omnipath::contains_control_chars(path);
+        windows::has_reserved_device_name(Path::new(path));
+    });
+}
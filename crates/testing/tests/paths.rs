@@ -0,0 +1,421 @@
+use omnipath::windows::{clean_utf8, is_win_clean, WinUtf8Path};
+
+#[test]
+fn test_clean_utf8() {
+    for (input, expected) in CLEAN {
+        assert_eq!(clean_utf8(input), *expected, "{:?}", input);
+        // Cleaning is idempotent.
+        assert_eq!(clean_utf8(expected), *expected, "{:?}", expected);
+    }
+}
+
+#[test]
+fn test_clean_keeps_device_spelling() {
+    // Cleaning never changes the `.` or `?` of a device path.
+    assert_eq!(clean_utf8("//./pipe/x"), r"\\.\pipe\x");
+    assert_eq!(clean_utf8("//?/pipe/x"), r"\\?\pipe\x");
+    assert_eq!(clean_utf8(r"\\?/pipe/./x"), r"\\?\pipe\x");
+    assert_eq!(clean_utf8(r"/\./pipe\.\x"), r"\\.\pipe\x");
+}
+
+static CLEAN: &[(&str, &str)] = &[
+    // Relative paths.
+    ("", "."),
+    (".", "."),
+    (r".\", "."),
+    ("file.txt", "file.txt"),
+    (r"path\to\file.txt", r"path\to\file.txt"),
+    ("path/to/file.txt", r"path\to\file.txt"),
+    (r"path\\to\\\file.txt", r"path\to\file.txt"),
+    (r"path\.\to\.\file.txt", r"path\to\file.txt"),
+    (r"path\to\..\file.txt", r"path\file.txt"),
+    (r"path\..", "."),
+    (r"..\file.txt", r"..\file.txt"),
+    (r"..\..\file.txt", r"..\..\file.txt"),
+    (r"path\..\..\file.txt", r"..\file.txt"),
+    (r"path\to\", r"path\to\"),
+    (r"path\to\.", r"path\to"),
+    // Trailing dots and spaces.
+    ("file.txt.", "file.txt"),
+    ("file.txt. . ", "file.txt"),
+    (r"path. \to..\file", r"path. \to..\file"),
+    (r"path.\to. .\file", r"path\to. \file"),
+    (r"path\...\file", r"path\...\file"),
+    (r"path\dir.\", r"path\dir\"),
+    (r"path\dir. \", r"path\dir. \"),
+    (r"path\...", r"path\"),
+    (r"path\file. \..", "path"),
+    (r"..\. .", r"..\"),
+    // Drive paths.
+    (r"C:\", r"C:\"),
+    ("C:/", r"C:\"),
+    (r"C:\path\to\file.txt", r"C:\path\to\file.txt"),
+    (r"C:\path\..\..\file.txt", r"C:\file.txt"),
+    (r"C:\..", r"C:\"),
+    (r"C:\path\to\", r"C:\path\to\"),
+    // Drive relative paths.
+    ("C:", "C:"),
+    ("C:file.txt", "C:file.txt"),
+    (r"C:path\..\..\file.txt", r"C:..\file.txt"),
+    // Root relative paths.
+    (r"\", r"\"),
+    (r"\path\..\..\file.txt", r"\file.txt"),
+    ("/path/to/file.txt", r"\path\to\file.txt"),
+    // UNC paths.
+    (r"\\server\share", r"\\server\share"),
+    (r"\\server\share\", r"\\server\share\"),
+    (r"\\server\share\path\..\..\file.txt", r"\\server\share\file.txt"),
+    ("//server/share/file.txt", r"\\server\share\file.txt"),
+    // Device paths.
+    (r"\\.\pipe\name", r"\\.\pipe\name"),
+    (r"\\.\pipe\..\name", r"\\.\name"),
+    (r"\\.\pipe\..\..", r"\\.\"),
+    (r"\\.\COM1.", r"\\.\COM1"),
+    (r"//./C:/path/../file.txt", r"\\.\C:\file.txt"),
+    ("//?/C:/path/../file.txt", r"\\?\C:\file.txt"),
+    // Verbatim paths are unchanged.
+    (r"\\?\C:\path\.\..\file.txt. ", r"\\?\C:\path\.\..\file.txt. "),
+    (r"\\?\UNC\server\share\..", r"\\?\UNC\server\share\.."),
+];
+
+#[test]
+fn test_win_utf8_path() {
+    for (path, parent, file_name) in PARENTS {
+        let path = WinUtf8Path::from_utf8(path);
+        assert_eq!(path.parent().map(|p| p.as_utf8()), *parent, "{:?}", path);
+        assert_eq!(path.file_name(), *file_name, "{:?}", path);
+    }
+    for (path, joined, expected) in JOINS {
+        assert_eq!(WinUtf8Path::from_utf8(path).join(joined), *expected, "{:?}", (path, joined));
+    }
+    // `clean` is the same as `clean_utf8`.
+    for (input, expected) in CLEAN {
+        assert_eq!(WinUtf8Path::from_utf8(input).clean().as_utf8(), *expected, "{:?}", input);
+    }
+}
+
+static PARENTS: &[(&str, Option<&str>, Option<&str>)] = &[
+    ("", None, None),
+    ("file.txt", Some(""), Some("file.txt")),
+    (r"path\to\file.txt", Some(r"path\to"), Some("file.txt")),
+    (r"path/to//file.txt", Some("path/to"), Some("file.txt")),
+    (r"path\to\", Some("path"), Some("to")),
+    (r"path\..", Some("path"), None),
+    (r"C:\", None, None),
+    (r"C:\file.txt", Some(r"C:\"), Some("file.txt")),
+    (r"C:\path\file.txt", Some(r"C:\path"), Some("file.txt")),
+    ("C:", None, None),
+    ("C:file.txt", Some("C:"), Some("file.txt")),
+    (r"\file.txt", Some(r"\"), Some("file.txt")),
+    (r"\", None, None),
+    (r"\\server\share", None, None),
+    (r"\\server\share\", None, None),
+    (r"\\server\share\file.txt", Some(r"\\server\share\"), Some("file.txt")),
+    (r"\\.\pipe", None, None),
+    (r"\\.\pipe\name", Some(r"\\.\pipe\"), Some("name")),
+    (r"\\?\C:\", None, None),
+    (r"\\?\C:\file.txt", Some(r"\\?\C:\"), Some("file.txt")),
+    // `/` is not a separator in verbatim paths.
+    (r"\\?\C:\a/b", Some(r"\\?\C:\"), Some("a/b")),
+    (r"\\?\UNC\server\share", None, None),
+    (r"\\?\UNC\server\share\file.txt", Some(r"\\?\UNC\server\share\"), Some("file.txt")),
+    (r"\\?\unc\server\share\file.txt", Some(r"\\?\unc\server\share\"), Some("file.txt")),
+];
+
+static JOINS: &[(&str, &str, &str)] = &[
+    ("", "file.txt", "file.txt"),
+    ("path", "file.txt", r"path\file.txt"),
+    (r"path\", "file.txt", r"path\file.txt"),
+    ("path/", "file.txt", "path/file.txt"),
+    ("C:", "file.txt", "C:file.txt"),
+    (r"C:\", "file.txt", r"C:\file.txt"),
+    (r"C:\path", r"\file.txt", r"C:\file.txt"),
+    (r"\\server\share\path", r"\file.txt", r"\\server\share\file.txt"),
+    (r"\\?\C:\path", r"\file.txt", r"\\?\C:\file.txt"),
+    ("path", r"\file.txt", r"\file.txt"),
+    (r"C:\path", r"D:\file.txt", r"D:\file.txt"),
+    (r"C:\path", "D:file.txt", "D:file.txt"),
+    (r"C:\path", r"\\server\share", r"\\server\share"),
+];
+
+#[test]
+fn test_to_verbatim_lexical() {
+    for (path, expected) in VERBATIM_LEXICAL {
+        let verbatim = WinUtf8Path::from_utf8(path).to_verbatim_lexical();
+        assert_eq!(verbatim.as_ref().map(|p| p.as_utf8()), *expected, "{:?}", path);
+    }
+}
+
+/// Paths and their lexical verbatim form, or `None` if the OS is needed.
+static VERBATIM_LEXICAL: &[(&str, Option<&str>)] = &[
+    (r"C:\", Some(r"\\?\C:\")),
+    (r"C:\data\file.txt", Some(r"\\?\C:\data\file.txt")),
+    (r"C:\data\dir\", Some(r"\\?\C:\data\dir\")),
+    (r"\\server\share", Some(r"\\?\UNC\server\share")),
+    (r"\\server\share\file.txt", Some(r"\\?\UNC\server\share\file.txt")),
+    (r"\\?\C:\data\..\file.", Some(r"\\?\C:\data\..\file.")),
+    // Lowercase drive letters are left to the OS.
+    (r"c:\data\file.txt", None),
+    // Relative paths.
+    ("", None),
+    (r"data\file.txt", None),
+    (r"C:data\file.txt", None),
+    (r"\data\file.txt", None),
+    // Paths that need cleaning.
+    ("C:/data/file.txt", None),
+    (r"C:\data\\file.txt", None),
+    (r"C:\data\.\file.txt", None),
+    (r"C:\data\..\file.txt", None),
+    (r"C:\data\file.txt.", None),
+    (r"C:\data\file.txt ", None),
+    (r"C:\data\NUL", None),
+    (r"C:\data\com1.txt", None),
+    (r"\\server", None),
+    (r"\\server\\share", None),
+    // Device paths.
+    (r"\\.\pipe\name", None),
+];
+
+#[test]
+fn test_add_extension() {
+    let tests = [
+        ("file", "gz", Some("file.gz")),
+        ("file.tar", "gz", Some("file.tar.gz")),
+        ("file.tar", ".gz", Some("file.tar.gz")),
+        ("file.tar", "", Some("file.tar")),
+        (".hidden", "txt", Some(".hidden.txt")),
+        (r"C:\a\..\file.tar", "gz", Some(r"C:\a\..\file.tar.gz")),
+        (r"\\?\C:\dir/file", "txt", Some(r"\\?\C:\dir/file.txt")),
+        // No file name.
+        ("", "txt", None),
+        (r"dir\", "txt", None),
+        ("dir/", "txt", None),
+        (r"dir\..", "txt", None),
+        (r"C:\", "txt", None),
+        (r"\\server\share", "txt", None),
+    ];
+    for (path, ext, expected) in tests {
+        let added = WinUtf8Path::from_utf8(path).add_extension(ext);
+        assert_eq!(added.as_ref().map(|p| p.as_utf8()), expected, "{:?}", (path, ext));
+    }
+}
+
+#[test]
+fn test_is_win_clean() {
+    let clean = [
+        r"C:\path\to\file.txt",
+        r"C:\path\to\",
+        r"C:\",
+        r"C:file.txt",
+        r"path\to\file.txt",
+        r"..\..\file.txt",
+        r"C:..\file.txt",
+        r"\file.txt",
+        r".",
+        r"\\server\share",
+        r"\\server\share\",
+        r"\\server\share\file.txt",
+        r"\\.\pipe\name",
+        r"\\?\pipe\name",
+        r"\\?\C:\path\.\..\file. ",
+        r"C:\path\...file",
+        r"C:\path..\to \file.txt",
+        r"C:\path\...\file.txt",
+    ];
+    let dirty = [
+        "",
+        r".\file.txt",
+        r"C:\path\.\file.txt",
+        r"C:\path\..\file.txt",
+        r"C:\..\file.txt",
+        r"path\..\..\file.txt",
+        r"\..\file.txt",
+        r"C:\path\\file.txt",
+        r"C:\path/file.txt",
+        r"C:/path",
+        r"//server/share/file.txt",
+        r"\\server\share\\file.txt",
+        r"C:\path\file.txt.",
+        r"C:\path.\file.txt",
+        r"C:\path\file.txt ",
+        r"C:\path\...",
+        r"C:\path\\",
+        r".\",
+        r"\\.\pipe\..\name",
+    ];
+    for path in clean {
+        assert!(is_win_clean(path), "{:?}", path);
+        assert_eq!(clean_utf8(path), path);
+    }
+    for path in dirty {
+        assert!(!is_win_clean(path), "{:?}", path);
+        assert_ne!(clean_utf8(path), path);
+        assert!(is_win_clean(&clean_utf8(path)), "{:?}", path);
+    }
+}
+
+#[test]
+fn test_regions() {
+    // The regions are separated by `|` with `_` for a region that's `None`.
+    let tests = [
+        (r"C:\dir\file.txt", r"C:|\|dir\|file|.txt"),
+        (r"C:/dir/sub/file.txt", r"C:|/|dir/sub/|file|.txt"),
+        (r"C:file.txt", r"C:|_|_|file|.txt"),
+        (r"C:\", r"C:|\|_|_|_"),
+        (r"\dir\", r"_|\|dir\|_|_"),
+        (r"dir\file", r"_|_|dir\|file|_"),
+        (r"file.tar.gz", r"_|_|_|file.tar|.gz"),
+        (r".hidden", r"_|_|_|.hidden|_"),
+        (r"file.", r"_|_|_|file|."),
+        (r"dir\..", r"_|_|dir\..|_|_"),
+        (r"dir\.", r"_|_|dir\.|_|_"),
+        ("", r"_|_|_|_|_"),
+        (r"\\server\share\dir\file", r"\\|server\share\|dir\|file|_"),
+        (r"\\server\share", r"\\|server\share|_|_|_"),
+        (r"\\.\pipe\name", r"\\.\|pipe\|_|name|_"),
+        (r"\\?\C:\dir\file.txt", r"\\?\|C:\|dir\|file|.txt"),
+        (r"\\?\UNC\server\share\file", r"\\?\UNC\|server\share\|_|file|_"),
+        (r"\\?\unc\server\share", r"\\?\unc\|server\share|_|_|_"),
+        // Only `\` is a separator in verbatim paths.
+        (r"\\?\C:\dir/file.txt", r"\\?\|C:\|_|dir/file|.txt"),
+    ];
+    for (path, expected) in tests {
+        let regions = WinUtf8Path::from_utf8(path).regions();
+        let parts = [regions.prefix, regions.root, regions.parent, regions.stem, regions.extension];
+        let parts: Vec<&str> = parts.iter().map(|r| r.clone().map_or("_", |r| &path[r])).collect();
+        assert_eq!(parts.join("|"), expected, "{:?}", path);
+    }
+}
+
+#[test]
+fn test_needs_verbatim() {
+    let long = "a".repeat(256);
+    let tests = [
+        (r"C:\path\to\file.txt", false),
+        (r"C:\path\.\to\..\file.txt", false),
+        (r"C:\path\to\", false),
+        (r"path/to/file.txt", false),
+        (r"\\server\share\file.txt", false),
+        (r"\\.\pipe\name", false),
+        // The device name is part of the prefix.
+        (r"\\.\nul", false),
+        (r"C:\path\file.", true),
+        (r"C:\path.\file", true),
+        (r"C:\path\file ", true),
+        (r"C:\path\...", true),
+        (r"C:\path\nul", true),
+        (r"C:\path\COM1.txt", true),
+        (r"\\server\share\aux", true),
+        // Verbatim paths.
+        (r"\\?\C:\path\to\file.txt", false),
+        (r"\\?\UNC\server\share\file.txt", false),
+        (r"\\?\pipe\name", false),
+        (r"\\?\C:\path\", false),
+        (r"\\?\C:\path\..\file.txt", true),
+        (r"\\?\C:\path\.\file.txt", true),
+        (r"\\?\C:\path\\file.txt", true),
+        (r"\\?\C:\path/file.txt", true),
+        (r"\\?\C:\path\file.txt.", true),
+        (r"\\?\UNC\server\share\nul", true),
+        (r"\\?\", true),
+    ];
+    for (path, expected) in tests {
+        assert_eq!(WinUtf8Path::from_utf8(path).needs_verbatim(), expected, "{:?}", path);
+    }
+
+    // Long components.
+    assert!(!WinUtf8Path::from_utf8(&format!(r"C:\{}", &long[..255])).needs_verbatim());
+    assert!(WinUtf8Path::from_utf8(&format!(r"C:\{}", long)).needs_verbatim());
+
+    // Long paths. `MAX_PATH` is 259 UTF-16 code units.
+    let path = format!(r"C:\{}\{}", &long[..200], &long[..55]);
+    assert_eq!(path.len(), 259);
+    assert!(!WinUtf8Path::from_utf8(&path).needs_verbatim());
+    assert!(WinUtf8Path::from_utf8(&format!("{}a", path)).needs_verbatim());
+    // The verbatim prefix isn't counted.
+    assert!(!WinUtf8Path::from_utf8(&format!(r"\\?\{}", path)).needs_verbatim());
+    // Characters outside the BMP are two UTF-16 code units.
+    let path = format!(r"C:\{}\{}", &long[..200], "\u{1F600}".repeat(28));
+    assert!(WinUtf8Path::from_utf8(&path).needs_verbatim());
+}
+
+#[test]
+fn test_unc_server_share() {
+    let tests = [
+        (r"\\server\share\file.txt", Some("server"), Some("share")),
+        ("//server/share/file.txt", Some("server"), Some("share")),
+        (r"\\server/share", Some("server"), Some("share")),
+        (r"\\server\share\", Some("server"), Some("share")),
+        (r"\\server", Some("server"), None),
+        (r"\\server\", Some("server"), None),
+        (r"\\server\\file", Some("server"), None),
+        (r"\\::1\share", Some("::1"), Some("share")),
+        (r"\\fe80--1.ipv6-literal.net\share", Some("fe80--1.ipv6-literal.net"), Some("share")),
+        (r"\\?\UNC\server\share\file.txt", Some("server"), Some("share")),
+        (r"\\?\UNC\server", Some("server"), None),
+        (r"\\?\UNC\::1\share\", Some("::1"), Some("share")),
+        // `UNC` ignores case.
+        (r"\\?\unc\server\share\file.txt", Some("server"), Some("share")),
+        (r"\\?\Unc\server", Some("server"), None),
+        (r"\\?\unc", None, None),
+        (r"\\?\UNC", None, None),
+        // `/` isn't a separator in verbatim paths.
+        (r"\\?\UNC\server/share", Some("server/share"), None),
+        // Not UNC paths.
+        (r"\\", None, None),
+        (r"C:\server\share", None, None),
+        (r"\\.\pipe\name", None, None),
+        (r"\\?\C:\server\share", None, None),
+        (r"\server\share", None, None),
+    ];
+    for (path, server, share) in tests {
+        let path = WinUtf8Path::from_utf8(path);
+        assert_eq!(path.unc_server(), server, "{:?}", path);
+        assert_eq!(path.unc_share(), share, "{:?}", path);
+    }
+}
+
+#[test]
+fn test_to_shell_display() {
+    use omnipath::windows::to_shell_display;
+
+    let tests = [
+        (r"\\?\c:\Users\file.txt", r"C:\Users\file.txt"),
+        ("c:/Users/file.txt", r"C:\Users\file.txt"),
+        (r"c:\Users/file.txt", r"C:\Users\file.txt"),
+        ("c:file.txt", "C:file.txt"),
+        (r"C:\Users\file.txt", r"C:\Users\file.txt"),
+        ("//server/share/file", r"\\server\share\file"),
+        (r"\\?\UNC\server\share\file", r"\\server\share\file"),
+        (r"\\?\unc\server\share\file", r"\\server\share\file"),
+        ("//./pipe/name", r"\\.\pipe\name"),
+        ("//?/pipe/name", r"\\.\pipe\name"),
+        ("dir/file", r"dir\file"),
+        ("/dir/file", r"\dir\file"),
+        ("", ""),
+        // Verbatim paths that can't be converted are unchanged.
+        (r"\\?\c:\file.", r"\\?\c:\file."),
+        (r"\\?\c:\a/b", r"\\?\c:\a/b"),
+        (r"\\?\pipe\name", r"\\?\pipe\name"),
+    ];
+    for (path, expected) in tests {
+        assert_eq!(to_shell_display(path), expected, "{:?}", path);
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn test_split_path_list_quoted() {
+    use omnipath::split_path_list;
+
+    let tests: &[(&str, &[&str])] = &[
+        (r#"C:\bin;"C:\a;b""#, &[r"C:\bin", r"C:\a;b"]),
+        (r#""C:\Program Files\app";C:\bin"#, &[r"C:\Program Files\app", r"C:\bin"]),
+        (r#""C:\a;b";;"C:\c""#, &[r"C:\a;b", "", r"C:\c"]),
+        // Quotes that don't wrap the whole entry are kept.
+        (r#"C:\"a;b"\c"#, &[r#"C:\"a;b"\c"#]),
+    ];
+    for (list, expected) in tests {
+        assert_eq!(split_path_list(list).collect::<Vec<_>>(), *expected, "{:?}", list);
+    }
+}
@@ -0,0 +1,105 @@
+//! Running conversions in sequence.
+use omnipath::windows::{Pipeline, PipelineStep};
+
+/// Steps that always return a verbatim path.
+const VERBATIM: [PipelineStep; 2] = [PipelineStep::Verbatim, PipelineStep::VerbatimExact];
+
+#[test]
+fn pipeline_rejects_incoherent_orders() {
+    use PipelineStep::*;
+
+    for earlier in VERBATIM {
+        for later in [Clean, Absolute, Winuser, Verbatim, VerbatimExact, VerbatimIfNeeded] {
+            let pipeline = Pipeline::new().step(earlier).absolute().step(later);
+            let error = pipeline.check().unwrap_err();
+            assert_eq!((error.earlier, error.later), (earlier, Absolute));
+            let pipeline = Pipeline::new().step(earlier).step(later);
+            let error = pipeline.check().unwrap_err();
+            assert_eq!((error.earlier, error.later), (earlier, later));
+        }
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn pipeline_run_rejected() {
+    use std::path::Path;
+
+    for earlier in VERBATIM {
+        let pipeline = Pipeline::new().step(earlier).clean();
+        assert!(pipeline.run(Path::new(r"C:\file")).is_err());
+    }
+}
+
+#[test]
+fn pipeline_warnings() {
+    use PipelineStep::*;
+
+    let ok = [
+        vec![],
+        vec![Clean],
+        vec![Absolute, Verbatim],
+        vec![Winuser, Absolute, VerbatimIfNeeded],
+        vec![Clean, Winuser],
+        vec![Absolute, Clean],
+        // `verbatim_if_needed` usually returns the path unchanged.
+        vec![VerbatimIfNeeded, Winuser],
+        vec![VerbatimIfNeeded, Clean],
+        vec![VerbatimIfNeeded, Absolute],
+    ];
+    for steps in ok {
+        let pipeline = steps.iter().fold(Pipeline::new(), |p, &step| p.step(step));
+        assert!(pipeline.check().is_ok(), "{:?}", steps);
+        assert!(pipeline.warnings().is_empty(), "{:?}", steps);
+        assert_eq!(pipeline.steps(), steps);
+    }
+
+    let redundant = [
+        (Clean, Absolute),
+        (Absolute, Absolute),
+        (Clean, Clean),
+        (Winuser, Winuser),
+        (VerbatimIfNeeded, Verbatim),
+        (VerbatimIfNeeded, VerbatimExact),
+        (VerbatimIfNeeded, VerbatimIfNeeded),
+    ];
+    for (earlier, later) in redundant {
+        let pipeline = Pipeline::new().step(earlier).step(later);
+        assert!(pipeline.check().is_ok());
+        let warning = pipeline.warnings()[0];
+        assert_eq!((warning.earlier, warning.later), (earlier, later));
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn pipeline_run() {
+    use omnipath::windows::WinPathExt;
+    use std::path::Path;
+
+    let path = Path::new(r"C:\path\.\to\..\file.txt");
+    let output = Pipeline::new().run(path).unwrap();
+    assert_eq!(output.path, path);
+    assert!(output.trace.is_empty());
+
+    let output = Pipeline::new().absolute().verbatim().run(path).unwrap();
+    assert_eq!(output.path, Path::new(r"\\?\C:\path\file.txt"));
+    assert_eq!(
+        output.trace,
+        [
+            (PipelineStep::Absolute, r"C:\path\file.txt".into()),
+            (PipelineStep::Verbatim, r"\\?\C:\path\file.txt".into()),
+        ]
+    );
+
+    // Only long paths are made verbatim.
+    let output = Pipeline::new().absolute().verbatim_if_needed().run(path).unwrap();
+    assert_eq!(output.path, Path::new(r"C:\path\file.txt"));
+    let long = format!(r"C:\{}\file.txt", "a".repeat(250));
+    let output = Pipeline::new().verbatim_if_needed().run(Path::new(&long)).unwrap();
+    assert_eq!(output.path, Path::new(&long).to_verbatim().unwrap());
+
+    // Steps that follow `verbatim_if_needed` still run.
+    let output = Pipeline::new().verbatim_if_needed().clean().winuser().run(path).unwrap();
+    assert_eq!(output.path, Path::new(r"C:\path\file.txt"));
+}
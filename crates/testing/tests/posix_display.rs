@@ -0,0 +1,43 @@
+//! Displaying POSIX paths for shell commands.
+#![cfg(unix)]
+use omnipath::posix::PosixPathExt;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+#[test]
+fn display_hybrid() {
+    let tests = [
+        ("build/app.log", "build/app.log"),
+        ("/usr/local/bin/tool", "/usr/local/bin/tool"),
+        ("café.txt", "café.txt"),
+        ("dir/", "dir/"),
+        ("/", "/"),
+        ("", ""),
+        ("out dir/build/my file.txt", "out dir/build/'my file.txt'"),
+        ("src/*.rs", "src/'*.rs'"),
+        ("data/file[1].csv", "data/'file[1].csv'"),
+        ("dir/it's", r"dir/'it'\''s'"),
+        ("dir/tab\there", "dir/'tab\there'"),
+        ("dir/$HOME", "dir/'$HOME'"),
+        ("dir/a;b", "dir/'a;b'"),
+        ("~user", "'~user'"),
+        // Only the file name is quoted.
+        ("my dir/file.txt", "my dir/file.txt"),
+    ];
+    for (path, expected) in tests {
+        let display = Path::new(path).posix_display_hybrid();
+        assert_eq!(display.to_string(), expected, "{:?}", path);
+    }
+
+    let path = Path::new(OsStr::from_bytes(b"dir/\xFFfile"));
+    assert_eq!(path.posix_display_hybrid().to_string(), r"dir/$'\xfffile'");
+}
+
+#[test]
+fn display_hybrid_quote_all() {
+    for path in ["build/app.log", "out dir/my file.txt", "dir/it's"] {
+        let display = Path::new(path).posix_display_hybrid().quote_all(true);
+        assert_eq!(display.to_string(), omnipath::posix::sh_quote(Path::new(path)));
+    }
+}
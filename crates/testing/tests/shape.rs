@@ -0,0 +1,73 @@
+//! Path shapes must never contain any part of the path's names.
+use omnipath::shape;
+use std::path::Path;
+
+const MARKER: &str = "s3cr3t";
+
+static PATHS: &[&str] = &[
+    "s3cr3t",
+    "/s3cr3t",
+    "/home/s3cr3t/s3cr3t.txt",
+    "dir/s3cr3t./s3cr3t ",
+    "../s3cr3t/../s3cr3t",
+    "s3cr3t/😍s3cr3t/s3cr3t£",
+    r"C:\s3cr3t\file.txt",
+    r"\\s3cr3t\s3cr3t\s3cr3t",
+    r"\\?\C:\s3cr3t",
+    r"\\.\s3cr3t\s3cr3t",
+    "s3cr3t/s3cr3ts3cr3ts3cr3ts3cr3ts3cr3ts3cr3ts3cr3ts3cr3ts3cr3ts3cr3ts3cr3t",
+];
+
+#[test]
+fn shape_contains_no_names() {
+    for path in PATHS {
+        let shape = shape(Path::new(path));
+        let display = shape.to_string();
+        let debug = format!("{:?}", shape);
+        assert!(!display.contains(MARKER), "{:?}: {}", path, display);
+        assert!(!debug.contains(MARKER), "{:?}: {}", path, debug);
+        // No other part of a name should appear either.
+        for part in ["s3c", "cr3", "txt", "😍", "£"] {
+            assert!(!display.contains(part), "{:?}: {}", path, display);
+            assert!(!debug.contains(part), "{:?}: {}", path, debug);
+        }
+    }
+}
+
+#[test]
+fn shape_is_deterministic() {
+    for path in PATHS {
+        let a = shape(Path::new(path));
+        let b = shape(Path::new(path));
+        assert_eq!(a, b);
+        assert_eq!(a.to_string(), b.to_string());
+    }
+}
+
+#[test]
+fn shape_fields() {
+    let shape = shape(Path::new("dir/s3cr3t./😍"));
+    assert_eq!(shape.depth, 3);
+    assert_eq!(shape.max_component_len, "s3cr3t.".len());
+    assert_eq!(shape.length_histogram, [3, 0, 0, 0, 0]);
+    assert!(shape.non_ascii);
+    assert!(shape.trailing_dot);
+    assert!(!shape.is_verbatim());
+
+    // `..` counts towards the depth but not the component lengths.
+    let shape = omnipath::shape(Path::new("../a"));
+    assert_eq!(shape.depth, 2);
+    assert_eq!(shape.length_histogram, [1, 0, 0, 0, 0]);
+    assert_eq!(omnipath::shape(Path::new("a/../b")).depth, 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn shape_non_utf8() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    let shape = shape(Path::new(OsStr::from_bytes(b"/s3cr3t\xFF.")));
+    assert!(shape.non_ascii);
+    assert!(shape.trailing_dot);
+    assert!(!shape.to_string().contains(MARKER));
+}
@@ -0,0 +1,110 @@
+//! Splitting a path into its parent, file name and extension.
+#![cfg(windows)]
+use omnipath::windows::WinPathExt;
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn split_file() {
+    // (path, parent, file name, extension)
+    let tests = [
+        (r"C:\dir\file.txt", Some(r"C:\dir"), Some("file.txt"), Some("txt")),
+        (r"C:\dir\photo.JPG ", Some(r"C:\dir"), Some("photo.JPG"), Some("JPG")),
+        (r"C:\dir\archive...", Some(r"C:\dir"), Some("archive"), None),
+        (r"C:\dir\file.tar.gz. .", Some(r"C:\dir"), Some("file.tar.gz"), Some("gz")),
+        (r"C:\dir\.hidden", Some(r"C:\dir"), Some(".hidden"), None),
+        (r"C:\dir\. .", Some(r"C:\dir"), None, None),
+        ("dir/file.txt", Some("dir"), Some("file.txt"), Some("txt")),
+        ("file", Some(""), Some("file"), None),
+        (r"C:\", None, None, None),
+        (r"C:\dir\..", Some(r"C:\dir"), None, None),
+        // The parent of a device path is the device root.
+        (r"\\.\pipe\name", Some(r"\\.\pipe\"), Some("name"), None),
+        (r"\\server\share\file.txt", Some(r"\\server\share\"), Some("file.txt"), Some("txt")),
+        // Verbatim paths aren't trimmed.
+        (r"\\?\C:\dir\file. ", Some(r"\\?\C:\dir"), Some("file. "), Some(" ")),
+        (r"\\?\C:\dir\file.", Some(r"\\?\C:\dir"), Some("file."), Some("")),
+    ];
+    for (path, parent, file_name, extension) in tests {
+        let parts = Path::new(path).win_split_file().unwrap();
+        assert_eq!(parts.parent, parent.map(Path::new), "{path}");
+        assert_eq!(parts.file_name.as_deref(), file_name.map(AsRef::as_ref), "{path}");
+        assert_eq!(parts.extension.as_deref(), extension.map(AsRef::as_ref), "{path}");
+    }
+}
+
+#[test]
+fn split_file_not_unicode() {
+    let mut wide: Vec<u16> = r"C:\dir\".encode_utf16().collect();
+    wide.push(0xD800);
+    wide.extend(".txt. ".encode_utf16());
+    let path = OsString::from_wide(&wide);
+    let parts = Path::new(&path).win_split_file().unwrap();
+
+    wide.truncate(wide.len() - 2);
+    let file_name = OsString::from_wide(&wide[r"C:\dir\".len()..]);
+    assert_eq!(parts.file_name.as_deref(), Some(file_name.as_ref()));
+    assert_eq!(parts.extension.as_deref(), Some("txt".as_ref()));
+
+    assert!(Path::new("C:\\file\0.txt").win_split_file().is_err());
+}
+
+#[test]
+fn root_len() {
+    // (path, root)
+    let tests = [
+        (r"C:\dir\file.txt", Some(r"C:\")),
+        ("C:/dir", Some("C:/")),
+        (r"\\server\share\file.txt", Some(r"\\server\share\")),
+        // A UNC or device path doesn't need a separator after the prefix.
+        (r"\\server\share", Some(r"\\server\share")),
+        (r"\\.\pipe", Some(r"\\.\pipe")),
+        (r"\\.\pipe\", Some(r"\\.\pipe\")),
+        (r"\\?\UNC\server\share", Some(r"\\?\UNC\server\share")),
+        (r"\\?\C:\dir", Some(r"\\?\C:\")),
+        ("C:", None),
+        (r"\dir", None),
+        ("dir", None),
+    ];
+    for (path, root) in tests {
+        let path = Path::new(path);
+        let len = path.win_root_len();
+        assert_eq!(path.win_root(), root.map(PathBuf::from), "{:?}", path);
+        assert_eq!(len, root.map(str::len), "{:?}", path);
+        // The length can be used to slice the path.
+        if let Some(len) = len {
+            assert!(len <= path.as_os_str().len(), "{:?}", path);
+        }
+    }
+}
+
+#[test]
+fn regions() {
+    // The prefix and root of an absolute path are the same as `win_root`.
+    for path in [
+        r"C:\dir\file.txt",
+        r"\\server\share\dir\file.txt",
+        r"\\server\share",
+        r"\\.\pipe\name",
+        r"\\.\pipe",
+        r"\\?\C:\dir\file.txt",
+        r"\\?\UNC\server\share\file.txt",
+    ] {
+        let path = Path::new(path);
+        let regions = path.win_regions();
+        let root_end = regions.root.map(|r| r.end);
+        assert_eq!(root_end, path.win_root_len(), "{:?}", path);
+    }
+
+    // Offsets are into the `OsStr`, even if it's not valid Unicode.
+    let mut wide: Vec<u16> = r"C:\dir".encode_utf16().collect();
+    wide.push(0xD800);
+    wide.extend(r"\file.txt".encode_utf16());
+    let path = OsString::from_wide(&wide);
+    let regions = Path::new(&path).win_regions();
+    let lossy = path.to_string_lossy();
+    assert_eq!(regions.parent.map(|r| &lossy[r]), Some("dir\u{FFFD}\\"));
+    assert_eq!(regions.stem.map(|r| &lossy[r]), Some("file"));
+    assert_eq!(regions.extension.map(|r| r.end), Some(path.len()));
+}
@@ -0,0 +1,88 @@
+//! Converting verbatim paths that are not valid Unicode.
+#![cfg(windows)]
+use omnipath::windows::WinPathExt;
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::path::PathBuf;
+
+fn wide_path(prefix: &str, suffix: &str) -> PathBuf {
+    // A lone surrogate between the prefix and suffix.
+    let mut wide: Vec<u16> = prefix.encode_utf16().collect();
+    wide.push(0xD800);
+    wide.extend(suffix.encode_utf16());
+    OsString::from_wide(&wide).into()
+}
+
+#[test]
+fn winuser_lone_surrogate() {
+    let tests = [
+        (r"\\?\C:\dir\", r"C:\dir\"),
+        (r"\\?\UNC\server\share\", r"\\server\share\"),
+        (r"\\?\unc\server\share\", r"\\server\share\"),
+        (r"\\?\Unc\server\share\", r"\\server\share\"),
+        (r"\\?\pipe\", r"\\.\pipe\"),
+    ];
+    for (verbatim, win32) in tests {
+        let path = wide_path(verbatim, ".txt");
+        assert_eq!(path.to_winuser_path().unwrap(), wide_path(win32, ".txt"));
+    }
+}
+
+#[test]
+fn winuser_lone_surrogate_unchanged() {
+    // A `/` is part of the file name in a verbatim path.
+    let path = wide_path(r"\\?\C:\dir\", "/file.txt");
+    assert_eq!(path.to_winuser_path().unwrap(), path);
+
+    // A trailing dot would be removed by a win32 path.
+    let path = wide_path(r"\\?\C:\dir\", ".");
+    assert_eq!(path.to_winuser_path().unwrap(), path);
+
+    // A surrogate can't be a drive letter.
+    let path = wide_path(r"\\?\", r":\file.txt");
+    assert_eq!(path.to_winuser_path().unwrap(), wide_path(r"\\.\", r":\file.txt"));
+}
+
+#[test]
+fn winuser_unc_ignores_case() {
+    use std::path::Path;
+
+    for unc in ["UNC", "unc", "Unc"] {
+        let path = format!(r"\\?\{unc}\srv\share\f.txt");
+        assert_eq!(Path::new(&path).to_winuser_path().unwrap(), Path::new(r"\\srv\share\f.txt"));
+        // A bare `\\?\UNC` has no server so it's kept.
+        let path = format!(r"\\?\{unc}");
+        assert_eq!(Path::new(&path).to_winuser_path().unwrap(), Path::new(&path));
+    }
+}
+
+#[test]
+fn winuser_keeps_volume_paths() {
+    use omnipath::windows::WinVerbatimKind;
+    use std::path::Path;
+
+    let tests = [
+        (r"\\?\Volume{26a21bda-a627-11d7-9931-806e6f6e6963}\dir\file", WinVerbatimKind::VolumeGuid),
+        (r"\\?\GLOBALROOT\Device\HarddiskVolume3\dir\file", WinVerbatimKind::GlobalRoot),
+    ];
+    for (path, kind) in tests {
+        let path = Path::new(path);
+        assert_eq!(path.win_verbatim_kind(), Some(kind));
+        assert_eq!(path.to_winuser_path().unwrap(), path);
+    }
+    let path = wide_path(r"\\?\GLOBALROOT\Device\", "");
+    assert_eq!(path.win_verbatim_kind(), Some(WinVerbatimKind::GlobalRoot));
+    assert_eq!(path.to_winuser_path().unwrap(), path);
+    let path = wide_path(r"\\?\", r"\file");
+    assert_eq!(path.win_verbatim_kind(), Some(WinVerbatimKind::Other));
+    assert_eq!(
+        Path::new(r"\\?\C:\file").win_verbatim_kind(),
+        Some(WinVerbatimKind::Drive(b'C' as u16))
+    );
+    assert_eq!(Path::new(r"\\.\pipe\name").win_verbatim_kind(), None);
+
+    // Only volume GUID paths can be converted.
+    for path in [r"C:\file", r"\\?\C:\file", r"\\?\GLOBALROOT\Device\HarddiskVolume3"] {
+        assert!(Path::new(path).win_volume_to_drive().is_err(), "{:?}", path);
+    }
+}